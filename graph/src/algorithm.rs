@@ -1,155 +1,1406 @@
-use crate::node::{Graph, Node};
-use std::collections::{BTreeMap, BTreeSet};
+use crate::node::{Graph, Link, Weight};
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BTreeSet, BinaryHeap};
 
-pub fn dijkstra<T: Eq + Ord>(gh: &Graph<T>, source: usize, look: usize) -> Option<BTreeMap<usize, usize>> {
-    let (_, path) = dijkstra_extra(gh, source, look);
-    path
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchResult {
+    pub path: Vec<usize>,
+    pub cost: Weight,
+    pub visited: BTreeSet<usize>,
 }
 
-pub fn dijkstra_extra<T: Eq + Ord>(gh: &Graph<T>, source: usize, look: usize) -> (Vec<Vec<usize>>, Option<BTreeMap<usize,usize>>) {
-    let src = gh.area[&source].borrow();
-    if src.edges.is_none(){
-        return (Vec::new(), None);
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SearchError {
+    UnknownSource(usize),
+    UnknownTarget(usize),
+    Unreachable(usize, usize),
+    BlockedSource(usize),
+    // a link which can't be handled by the search, e.g. a negative one in Dijkstra's
+    NegativeWeight(usize, usize),
+    NegativeCycle(Vec<usize>),
+    // the graph is too big for a table of every pair of nodes, the limit is given
+    TooManyNodes(usize, usize),
+    // a sum of weights along a path doesn't fit a weight
+    CostOverflow,
+}
+
+impl std::fmt::Display for SearchError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SearchError::UnknownSource(i) => write!(f, "there's no source node {}", i),
+            SearchError::UnknownTarget(i) => write!(f, "there's no target node {}", i),
+            SearchError::Unreachable(from, to) => write!(f, "node {} is unreachable from {}", to, from),
+            SearchError::BlockedSource(i) => write!(f, "source node {} is blocked", i),
+            SearchError::NegativeWeight(from, to) => write!(f, "link {} -> {} has a negative weight", from, to),
+            SearchError::NegativeCycle(cycle) => {
+                let cycle = cycle.iter().map(|i| i.to_string()).collect::<Vec<String>>();
+                write!(f, "there's a negative cycle {}", cycle.join(" -> "))
+            }
+            SearchError::TooManyNodes(count, limit) => write!(f, "too many nodes for a distance table, {} of at most {}", count, limit),
+            SearchError::CostOverflow => write!(f, "the cost of a path is too large"),
+        }
+    }
+}
+
+impl std::error::Error for SearchError {}
+
+pub fn dijkstra<T: Eq + Ord>(gh: &Graph<T>, source: usize, look: usize) -> Result<SearchResult, SearchError> {
+    let (_, result) = dijkstra_extra(gh, source, look);
+    result
+}
+
+pub fn dijkstra_extra<T: Eq + Ord>(gh: &Graph<T>, source: usize, look: usize) -> (Vec<Vec<usize>>, Result<SearchResult, SearchError>) {
+    if gh.node_by_index(look).is_none() {
+        return (Vec::new(), Err(SearchError::UnknownTarget(look)));
+    }
+
+    match shortest_paths(gh, source, Some(look)) {
+        (steps, Ok(paths)) => {
+            let result = paths.search_result(source, look);
+            (steps, result)
+        }
+        (steps, Err(err)) => (steps, Err(err)),
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ShortestPaths {
+    pub dist: BTreeMap<usize, Weight>,
+    pub prev: BTreeMap<usize, usize>,
+    pub visited: BTreeSet<usize>,
+}
+
+impl ShortestPaths {
+    // the path is ordered from the source to `to`
+    pub fn path_to(&self, to: usize) -> Option<Vec<usize>> {
+        if !self.dist.contains_key(&to) {
+            return None;
+        }
+
+        let mut path = vec![to];
+        let mut current = to;
+        while let Some(&prev) = self.prev.get(&current) {
+            path.push(prev);
+            current = prev;
+        }
+        path.reverse();
+
+        Some(path)
+    }
+
+    pub fn search_result(&self, source: usize, to: usize) -> Result<SearchResult, SearchError> {
+        match self.path_to(to) {
+            Some(path) => Ok(SearchResult {
+                path,
+                cost: self.dist[&to],
+                visited: self.visited.clone(),
+            }),
+            None => Err(SearchError::Unreachable(source, to)),
+        }
+    }
+}
+
+fn check_endpoints<T: Eq + Ord>(gh: &Graph<T>, source: usize, target: usize) -> Result<(), SearchError> {
+    check_source(gh, source)?;
+    match gh.node_by_index(target) {
+        Some(_) => Ok(()),
+        None => Err(SearchError::UnknownTarget(target)),
+    }
+}
+
+fn add_cost(cost: Weight, weight: Weight) -> Result<Weight, SearchError> {
+    cost.checked_add(weight).ok_or(SearchError::CostOverflow)
+}
+
+fn check_source<T: Eq + Ord>(gh: &Graph<T>, source: usize) -> Result<(), SearchError> {
+    match gh.node_by_index(source) {
+        Some(node) if !node.blocked => Ok(()),
+        Some(_) => Err(SearchError::BlockedSource(source)),
+        None => Err(SearchError::UnknownSource(source)),
+    }
+}
+
+// Dijkstra's search on a binary heap.
+//
+// If `look` is set the search stops as soon as it's settled, otherwise the whole
+// reachable part of the graph is explored.
+// The first element of the tuple is an exploration trace, each step holds
+// a settled node followed by the neighbors which were examined from it.
+pub fn shortest_paths<T: Eq + Ord>(gh: &Graph<T>, source: usize, look: Option<usize>) -> (Vec<Vec<usize>>, Result<ShortestPaths, SearchError>) {
+    if let Err(err) = check_source(gh, source) {
+        return (Vec::new(), Err(err));
     }
 
-    let mut rev = BTreeMap::new();
-    let mut checked = BTreeSet::new();
-    let mut unchecked = BTreeSet::new();
-    let mut dist = BTreeMap::new();
-    unchecked.insert(source);
-    dist.insert(source, 0);
+    run_dijkstra(source, look, &|n| gh.neighbors(n))
+}
+
+// Dijkstra's search backwards, from every node to the target.
+//
+// Predecessors are reversed as well, so they point to the next node on a way to the target.
+pub fn shortest_paths_to<T: Eq + Ord>(gh: &Graph<T>, target: usize) -> Result<ShortestPaths, SearchError> {
+    if gh.node_by_index(target).is_none() {
+        return Err(SearchError::UnknownTarget(target));
+    }
+
+    let incoming = reversed_links(gh);
+    let (_, paths) = run_dijkstra(target, None, &|n| incoming.get(&n).cloned().unwrap_or_default());
+    paths
+}
+
+fn run_dijkstra(source: usize, look: Option<usize>, links: &dyn Fn(usize) -> Vec<(usize, Weight)>) -> (Vec<Vec<usize>>, Result<ShortestPaths, SearchError>) {
+    let mut paths = ShortestPaths::default();
+    let mut queue = BinaryHeap::new();
+    paths.dist.insert(source, 0);
+    queue.push(Reverse((0, source)));
 
     let mut iteration_info = Vec::new();
 
-    while !unchecked.is_empty() {
-        let (u, weight) = match dist.iter().filter(|&(e, _)| !checked.contains(e)).min_by(|(_, lw), (_, rw)| lw.cmp(&rw)) {
-            Some(d) => (*d.0, *d.1),
-            None => break,
-        };
-        unchecked.remove(&u);
+    while let Some(Reverse((weight, u))) = queue.pop() {
+        // the heap may keep outdated entries for nodes which were improved later
+        if !paths.visited.insert(u) {
+            continue;
+        }
 
-        // what about case where rev.contains(look)?
-        // I get it to be insufficient, since it can be not shortest way.
-        if u == look {
-            return (iteration_info, Some(rev));
+        if Some(u) == look {
+            break;
         }
 
-        iteration_info.push(vec![u]);
+        let mut step = vec![u];
+        for (v, w) in links(u) {
+            step.push(v);
+
+            if w < 0 {
+                return (iteration_info, Err(SearchError::NegativeWeight(u, v)));
+            }
 
-        let u_node = gh.node_by_index(u).unwrap();
-        let u_node = u_node.borrow();
-        let edges = match u_node.edges.as_ref() {
-            Some(e) => e,
-            None => {
-                checked.insert(u);
+            if paths.visited.contains(&v) {
                 continue;
             }
+
+            let weight_from_source = match add_cost(weight, w) {
+                Ok(weight) => weight,
+                Err(err) => return (iteration_info, Err(err)),
+            };
+            let improved = paths.dist.get(&v).is_none_or(|&old_weight| weight_from_source < old_weight);
+            if improved {
+                paths.dist.insert(v, weight_from_source);
+                paths.prev.insert(v, u);
+                queue.push(Reverse((weight_from_source, v)));
+            }
+        }
+
+        iteration_info.push(step);
+    }
+
+    (iteration_info, Ok(paths))
+}
+
+pub fn a_star<T: Eq + Ord, H: Fn(usize) -> Weight>(gh: &Graph<T>, start: usize, goal: usize, h: H) -> Result<SearchResult, SearchError> {
+    let (_, result) = a_star_extra(gh, start, goal, h);
+    result
+}
+
+pub fn a_star_extra<T: Eq + Ord, H: Fn(usize) -> Weight>(gh: &Graph<T>, start: usize, goal: usize, h: H) -> (Vec<Vec<usize>>, Result<SearchResult, SearchError>) {
+    if let Err(err) = check_endpoints(gh, start, goal) {
+        return (Vec::new(), Err(err));
+    }
+
+    let mut paths = ShortestPaths::default();
+    paths.dist.insert(start, 0);
+
+    let mut open_set = BinaryHeap::new();
+    open_set.push(Reverse((h(start), 0, start)));
+
+    let mut iteration_info = Vec::new();
+
+    while let Some(Reverse((_, g_score, current))) = open_set.pop() {
+        // skip entries which were improved after they had been pushed,
+        // a node still may be reopened in case the heuristic is inconsistent
+        if g_score > paths.dist[&current] {
+            continue;
+        }
+
+        paths.visited.insert(current);
+        if current == goal {
+            let result = paths.search_result(start, goal);
+            return (iteration_info, result);
+        }
+
+        let mut step = vec![current];
+        for (neighbor, weight) in gh.neighbors(current) {
+            if weight < 0 {
+                return (iteration_info, Err(SearchError::NegativeWeight(current, neighbor)));
+            }
+
+            step.push(neighbor);
+
+            let tentative_g_score = match add_cost(g_score, weight) {
+                Ok(score) => score,
+                Err(err) => return (iteration_info, Err(err)),
+            };
+            let improved = paths.dist.get(&neighbor).is_none_or(|&score| tentative_g_score < score);
+            if improved {
+                paths.dist.insert(neighbor, tentative_g_score);
+                paths.prev.insert(neighbor, current);
+                // the estimate only orders the queue, so it may saturate
+                open_set.push(Reverse((tentative_g_score.saturating_add(h(neighbor)), tentative_g_score, neighbor)));
+            }
+        }
+
+        iteration_info.push(step);
+    }
+
+    (iteration_info, Err(SearchError::Unreachable(start, goal)))
+}
+
+// Bidirectional A*, one search goes from the start and another one goes back from the goal.
+//
+// `forward_h` estimates a distance to the goal, `backward_h` a distance from the start.
+// Both heuristics have to be consistent for the path to be the shortest one,
+// `|_| 0` turns it into a bidirectional Dijkstra's search.
+pub fn bidirectional_a_star<T: Eq + Ord, F: Fn(usize) -> Weight, B: Fn(usize) -> Weight>(gh: &Graph<T>, start: usize, goal: usize, forward_h: F, backward_h: B) -> Result<SearchResult, SearchError> {
+    let (_, result) = bidirectional_a_star_extra(gh, start, goal, forward_h, backward_h);
+    result
+}
+
+pub fn bidirectional_a_star_extra<T: Eq + Ord, F: Fn(usize) -> Weight, B: Fn(usize) -> Weight>(gh: &Graph<T>, start: usize, goal: usize, forward_h: F, backward_h: B) -> (Vec<Vec<usize>>, Result<SearchResult, SearchError>) {
+    if let Err(err) = check_endpoints(gh, start, goal) {
+        return (Vec::new(), Err(err));
+    }
+
+    let incoming = reversed_links(gh);
+    let outgoing = |n: usize| gh.neighbors(n);
+    let incoming = |n: usize| incoming.get(&n).cloned().unwrap_or_default();
+
+    let mut forward = Frontier::new(start, forward_h(start));
+    let mut backward = Frontier::new(goal, backward_h(goal));
+    // the cost of the best path found so far and a node where both searches met on it
+    let mut best = if start == goal { Some((0, start)) } else { None };
+
+    let mut iteration_info = Vec::new();
+    while let (Some(forward_top), Some(backward_top)) = (forward.top(), backward.top()) {
+        if let Some((cost, _)) = best {
+            if forward_top >= cost || backward_top >= cost {
+                break;
+            }
+        }
+
+        let step = if forward_top <= backward_top {
+            forward.expand(&backward, &outgoing, &forward_h, &mut best)
+        } else {
+            backward.expand(&forward, &incoming, &backward_h, &mut best)
+        };
+
+        match step {
+            Ok(Some(step)) => iteration_info.push(step),
+            Ok(None) => (),
+            Err(err) => return (iteration_info, Err(err)),
+        }
+    }
+
+    let (cost, middle) = match best {
+        Some(best) => best,
+        None => return (iteration_info, Err(SearchError::Unreachable(start, goal))),
+    };
+
+    let mut path = forward.paths.path_to(middle).unwrap_or_default();
+    let mut back_path = backward.paths.path_to(middle).unwrap_or_default();
+    back_path.pop();
+    back_path.reverse();
+    path.extend(back_path);
+
+    let visited = forward.paths.visited.union(&backward.paths.visited).cloned().collect();
+
+    (iteration_info, Ok(SearchResult { path, cost, visited }))
+}
+
+struct Frontier {
+    paths: ShortestPaths,
+    queue: BinaryHeap<Reverse<(Weight, Weight, usize)>>,
+}
+
+impl Frontier {
+    fn new(source: usize, h: Weight) -> Self {
+        let mut paths = ShortestPaths::default();
+        paths.dist.insert(source, 0);
+
+        let mut queue = BinaryHeap::new();
+        queue.push(Reverse((h, 0, source)));
+
+        Frontier { paths, queue }
+    }
+
+    fn top(&self) -> Option<Weight> {
+        self.queue.peek().map(|Reverse((f_score, _, _))| *f_score)
+    }
+
+    // expands the top node, the step is None if the node was outdated
+    fn expand(
+        &mut self,
+        other: &Frontier,
+        links: &dyn Fn(usize) -> Vec<(usize, Weight)>,
+        h: &dyn Fn(usize) -> Weight,
+        best: &mut Option<(Weight, usize)>,
+    ) -> Result<Option<Vec<usize>>, SearchError> {
+        let Reverse((_, g_score, current)) = match self.queue.pop() {
+            Some(top) => top,
+            None => return Ok(None),
         };
 
-        for child in edges {
-            let check_node = &child.to.borrow();
-            let weight_from_source = child.weight + weight;
+        if g_score > self.paths.dist[&current] {
+            return Ok(None);
+        }
+
+        self.paths.visited.insert(current);
 
-            rev.entry(check_node.index_in).or_insert(u);
+        let mut step = vec![current];
+        for (neighbor, weight) in links(current) {
+            if weight < 0 {
+                return Err(SearchError::NegativeWeight(current, neighbor));
+            }
+
+            step.push(neighbor);
+
+            let tentative_g_score = add_cost(g_score, weight)?;
+            let improved = self.paths.dist.get(&neighbor).is_none_or(|&score| tentative_g_score < score);
+            if !improved {
+                continue;
+            }
 
-            dist.entry(check_node.index_in).and_modify(|old_weight| {
-                if weight_from_source < *old_weight {
-                    *old_weight = weight_from_source;
+            self.paths.dist.insert(neighbor, tentative_g_score);
+            self.paths.prev.insert(neighbor, current);
+            self.queue.push(Reverse((tentative_g_score.saturating_add(h(neighbor)), tentative_g_score, neighbor)));
 
-                    *rev.get_mut(&check_node.index_in).unwrap() = u;
+            if let Some(&other_score) = other.paths.dist.get(&neighbor) {
+                let cost = add_cost(tentative_g_score, other_score)?;
+                if best.is_none_or(|(best_cost, _)| cost < best_cost) {
+                    *best = Some((cost, neighbor));
                 }
-            }).or_insert(weight_from_source);
+            }
+        }
 
-            unchecked.insert(check_node.index_in);
+        Ok(Some(step))
+    }
+}
 
-            iteration_info.last_mut().unwrap().push(check_node.index_in);
+// incoming links of each node, as pairs of a node where a link starts and its weight
+fn reversed_links<T: Eq + Ord>(gh: &Graph<T>) -> BTreeMap<usize, Vec<(usize, Weight)>> {
+    let mut incoming: BTreeMap<usize, Vec<(usize, Weight)>> = BTreeMap::new();
+    for node in gh.indices() {
+        for (neighbor, weight) in gh.neighbors(node) {
+            incoming.entry(neighbor).or_default().push((node, weight));
         }
+    }
 
-        checked.insert(u);
+    incoming
+}
+
+pub fn bellman_ford<T: Eq + Ord>(gh: &Graph<T>, source: usize, look: usize) -> Result<SearchResult, SearchError> {
+    if gh.node_by_index(look).is_none() {
+        return Err(SearchError::UnknownTarget(look));
     }
 
-    (iteration_info, Some(rev))
+    bellman_ford_paths(gh, source)?.search_result(source, look)
 }
 
-pub fn a_star<T: Eq + Ord, H: Fn(usize) -> usize>(gh: &Graph<T>, start: usize, goal: usize, h: H) -> Option<BTreeMap<usize, usize>> {
-    let d = | node: &Node<T>, i: usize | -> usize {
-        node.edges.as_ref().unwrap()[i].weight
+// Bellman-Ford's search over the part of the graph reachable from the source.
+//
+// In case a negative cycle is reachable it's returned as an error,
+// the cycle is ordered in the direction of links.
+pub fn bellman_ford_paths<T: Eq + Ord>(gh: &Graph<T>, source: usize) -> Result<ShortestPaths, SearchError> {
+    check_source(gh, source)?;
+
+    let mut paths = ShortestPaths::default();
+    paths.dist.insert(source, 0);
+
+    let links = gh.indices().flat_map(|u| gh.neighbors(u).into_iter().map(move |(v, w)| (u, v, w))).collect::<Vec<_>>();
+    let relax = |paths: &mut ShortestPaths| -> Result<Option<usize>, SearchError> {
+        let mut relaxed = None;
+        for &(u, v, w) in &links {
+            let weight_from_source = match paths.dist.get(&u) {
+                Some(&weight) => add_cost(weight, w)?,
+                None => continue,
+            };
+
+            if paths.dist.get(&v).is_none_or(|&old_weight| weight_from_source < old_weight) {
+                paths.dist.insert(v, weight_from_source);
+                paths.prev.insert(v, u);
+                relaxed = Some(v);
+            }
+        }
+
+        Ok(relaxed)
     };
 
-    let mut open_set = BTreeSet::new();
-    open_set.insert(start);
+    for _ in 1..gh.len() {
+        if relax(&mut paths)?.is_none() {
+            break;
+        }
+    }
+
+    if let Some(relaxed) = relax(&mut paths)? {
+        return Err(SearchError::NegativeCycle(negative_cycle(&paths.prev, relaxed, gh.len())));
+    }
+
+    paths.visited = paths.dist.keys().cloned().collect();
 
-    let mut came_from = BTreeMap::new();
-    
-    let infinity_value = usize::max_value() / 2;
-    let mut g_score = BTreeMap::new();
-    for i in 0 .. gh.area.len() {
-        g_score.insert(i, infinity_value);
+    Ok(paths)
+}
+
+fn negative_cycle(prev: &BTreeMap<usize, usize>, relaxed: usize, count: usize) -> Vec<usize> {
+    // a node relaxed on the last iteration might only be reachable from the cycle,
+    // but stepping back by the count of nodes guarantees to end up on the cycle.
+    let mut start = relaxed;
+    for _ in 0..count {
+        start = prev[&start];
+    }
+
+    let mut cycle = vec![start];
+    let mut current = prev[&start];
+    while current != start {
+        cycle.push(current);
+        current = prev[&current];
+    }
+    cycle.reverse();
+
+    cycle
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AllPairs {
+    // indexes of nodes in the order of rows and columns
+    pub nodes: Vec<usize>,
+    pub dist: Vec<Vec<Option<Weight>>>,
+    pub next: Vec<Vec<Option<usize>>>,
+}
+
+impl AllPairs {
+    fn position(&self, node: usize) -> Option<usize> {
+        self.nodes.iter().position(|&n| n == node)
     }
-    g_score.entry(start).and_modify(|s| *s = 0);
 
-    let mut f_score = BTreeMap::new();
-    for i in 0 .. gh.area.len() {
-        f_score.insert(i, infinity_value);
+    pub fn distance(&self, from: usize, to: usize) -> Option<Weight> {
+        self.dist[self.position(from)?][self.position(to)?]
     }
-    f_score.entry(start).and_modify(|s| *s = h(start));
 
-    while !open_set.is_empty() {
-        let max_in_f_score = f_score.iter().map(|s| (*s.0, *s.1)).max_by(|lhs, rhs| lhs.1.cmp(&rhs.1)).unwrap();
-        let mut min = max_in_f_score.1;
-        let mut current = max_in_f_score.0;
-        for open in &open_set {
-            if f_score[open] <= min {
-                min = f_score[open];
-                current = *open;
+    pub fn path(&self, from: usize, to: usize) -> Option<Vec<usize>> {
+        let to_position = self.position(to)?;
+        let mut current = self.position(from)?;
+        self.next[current][to_position]?;
+
+        let mut path = vec![from];
+        while current != to_position {
+            let next = self.next[current][to_position]?;
+            path.push(next);
+            current = self.position(next)?;
+        }
+
+        Some(path)
+    }
+}
+
+// the tables grow as a square and the search as a cube of the number of nodes
+pub const ALL_PAIRS_LIMIT: usize = 256;
+
+pub fn floyd_warshall<T: Eq + Ord>(gh: &Graph<T>) -> Result<AllPairs, SearchError> {
+    let nodes = gh.indices().collect::<Vec<usize>>();
+    if nodes.len() > ALL_PAIRS_LIMIT {
+        return Err(SearchError::TooManyNodes(nodes.len(), ALL_PAIRS_LIMIT));
+    }
+    let positions = nodes.iter().enumerate().map(|(i, &node)| (node, i)).collect::<BTreeMap<usize, usize>>();
+    let count = nodes.len();
+
+    let mut dist = vec![vec![None; count]; count];
+    let mut next = vec![vec![None; count]; count];
+    for (i, &node) in nodes.iter().enumerate() {
+        dist[i][i] = Some(0);
+        next[i][i] = Some(node);
+
+        for (neighbor, weight) in gh.neighbors(node) {
+            let j = positions[&neighbor];
+            if dist[i][j].is_none_or(|old_weight| weight < old_weight) {
+                dist[i][j] = Some(weight);
+                next[i][j] = Some(neighbor);
             }
         }
+    }
 
-        if current == goal {
-            return Some(came_from);
+    for k in 0..count {
+        for i in 0..count {
+            let to_k = match dist[i][k] {
+                Some(weight) => weight,
+                None => continue,
+            };
+
+            for j in 0..count {
+                let through_k = match dist[k][j] {
+                    Some(weight) => add_cost(to_k, weight)?,
+                    None => continue,
+                };
+
+                if dist[i][j].is_none_or(|old_weight| through_k < old_weight) {
+                    dist[i][j] = Some(through_k);
+                    next[i][j] = next[i][k];
+                }
+            }
         }
+    }
 
-        open_set.remove(&current);
-        let node = gh.node_by_index(current).unwrap();
-        let node = node.borrow();
-        let edges = match node.edges.as_ref() {
-            Some(e) => e,
-            None => continue,
-        };
+    for (i, &node) in nodes.iter().enumerate() {
+        if dist[i][i].is_some_and(|weight| weight < 0) {
+            // the next hops are meaningless on a negative cycle so it's looked up separately
+            return match bellman_ford_paths(gh, node) {
+                Err(err) => Err(err),
+                Ok(_) => Err(SearchError::NegativeCycle(vec![node])),
+            };
+        }
+    }
+
+    Ok(AllPairs { nodes, dist, next })
+}
+
+pub struct SpanningTree {
+    pub links: Vec<Link>,
+    pub weight: Weight,
+}
+
+impl SpanningTree {
+    // pairs of indexes of nodes which are connected by the links
+    pub fn pairs(&self) -> Vec<(usize, usize)> {
+        self.links.iter().map(|link| (link.from, link.to)).collect()
+    }
+}
+
+// Links of the graph are considered as undirected ones by spanning tree algorithms,
+// if the graph isn't connected a tree is built for each of its components.
+pub fn prim<T: Eq + Ord>(gh: &Graph<T>) -> SpanningTree {
+    let links = all_links(gh);
+    let mut incident: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+    for (i, (from, to, _)) in links.iter().enumerate() {
+        incident.entry(*from).or_default().push(i);
+        incident.entry(*to).or_default().push(i);
+    }
+
+    let mut tree = SpanningTree { links: Vec::new(), weight: 0 };
+    let mut in_tree = BTreeSet::new();
+    for root in gh.indices() {
+        if !in_tree.insert(root) {
+            continue;
+        }
+
+        let mut queue = BinaryHeap::new();
+        queue.extend(incident.get(&root).into_iter().flatten().map(|&i| Reverse((links[i].2.weight, i))));
+        while let Some(Reverse((weight, i))) = queue.pop() {
+            let (from, to, link) = &links[i];
+            let next = if in_tree.contains(from) { *to } else { *from };
+            if !in_tree.insert(next) {
+                continue;
+            }
+
+            tree.links.push(*link);
+            tree.weight += weight;
+            queue.extend(incident.get(&next).into_iter().flatten().map(|&i| Reverse((links[i].2.weight, i))));
+        }
+    }
+
+    tree
+}
+
+pub fn kruskal<T: Eq + Ord>(gh: &Graph<T>) -> SpanningTree {
+    let mut links = all_links(gh);
+    links.sort_by_key(|(_, _, link)| link.weight);
+
+    let mut sets = DisjointSets::new(gh.indices());
+    let mut tree = SpanningTree { links: Vec::new(), weight: 0 };
+    for (from, to, link) in links {
+        if sets.union(from, to) {
+            tree.weight += link.weight;
+            tree.links.push(link);
+        }
+    }
+
+    tree
+}
+
+fn all_links<T: Eq + Ord>(gh: &Graph<T>) -> Vec<(usize, usize, Link)> {
+    gh.indices().flat_map(|i| gh.links_from(i)).map(|link| (link.from, link.to, *link)).collect()
+}
+
+struct DisjointSets {
+    parents: BTreeMap<usize, usize>,
+}
+
+impl DisjointSets {
+    fn new(elements: impl Iterator<Item = usize>) -> Self {
+        DisjointSets {
+            parents: elements.map(|e| (e, e)).collect(),
+        }
+    }
+
+    fn find(&mut self, e: usize) -> usize {
+        let parent = self.parents[&e];
+        if parent == e {
+            return e;
+        }
+
+        let root = self.find(parent);
+        self.parents.insert(e, root);
+        root
+    }
+
+    // returns false if the elements already were in the same set
+    fn union(&mut self, lhs: usize, rhs: usize) -> bool {
+        let (lhs, rhs) = (self.find(lhs), self.find(rhs));
+        if lhs == rhs {
+            return false;
+        }
+
+        self.parents.insert(lhs, rhs);
+        true
+    }
+}
+
+// Components are ordered by their smallest nodes, nodes of a component are sorted.
+pub fn connected_components<T: Eq + Ord>(gh: &Graph<T>) -> Vec<Vec<usize>> {
+    let mut sets = DisjointSets::new(gh.indices());
+    for (from, to, _) in all_links(gh) {
+        sets.union(from, to);
+    }
+
+    let mut components: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+    for node in gh.indices() {
+        components.entry(sets.find(node)).or_default().push(node);
+    }
+
+    sort_components(components.into_values().collect())
+}
+
+// Tarjan's algorithm, it's iterative so large graphs don't overflow the stack.
+pub fn strongly_connected_components<T: Eq + Ord>(gh: &Graph<T>) -> Vec<Vec<usize>> {
+    let mut tarjan = Tarjan {
+        gh,
+        index: 0,
+        indices: BTreeMap::new(),
+        lowlinks: BTreeMap::new(),
+        stack: Vec::new(),
+        on_stack: BTreeSet::new(),
+        calls: Vec::new(),
+        components: Vec::new(),
+    };
+
+    for root in gh.indices() {
+        if !tarjan.indices.contains_key(&root) {
+            tarjan.run(root);
+        }
+    }
+
+    sort_components(tarjan.components)
+}
+
+struct Tarjan<'a, T: Eq + Ord> {
+    gh: &'a Graph<T>,
+    index: usize,
+    indices: BTreeMap<usize, usize>,
+    lowlinks: BTreeMap<usize, usize>,
+    stack: Vec<usize>,
+    on_stack: BTreeSet<usize>,
+    // emulated call stack, a node with its neighbors which are left to be checked
+    calls: Vec<(usize, Vec<usize>)>,
+    components: Vec<Vec<usize>>,
+}
+
+impl<'a, T: Eq + Ord> Tarjan<'a, T> {
+    fn run(&mut self, root: usize) {
+        self.visit(root);
+
+        while let Some((node, neighbors)) = self.calls.last_mut() {
+            let node = *node;
+            match neighbors.pop() {
+                Some(neighbor) if !self.indices.contains_key(&neighbor) => self.visit(neighbor),
+                Some(neighbor) => {
+                    if self.on_stack.contains(&neighbor) {
+                        self.lower(node, self.indices[&neighbor]);
+                    }
+                }
+                None => {
+                    self.calls.pop();
+                    if let Some(&(caller, _)) = self.calls.last() {
+                        self.lower(caller, self.lowlinks[&node]);
+                    }
+
+                    if self.lowlinks[&node] == self.indices[&node] {
+                        self.pop_component(node);
+                    }
+                }
+            }
+        }
+    }
+
+    fn visit(&mut self, node: usize) {
+        self.indices.insert(node, self.index);
+        self.lowlinks.insert(node, self.index);
+        self.index += 1;
+        self.stack.push(node);
+        self.on_stack.insert(node);
+
+        let mut neighbors = self.gh.neighbors(node).into_iter().map(|(n, _)| n).collect::<Vec<usize>>();
+        neighbors.reverse();
+        self.calls.push((node, neighbors));
+    }
+
+    fn lower(&mut self, node: usize, lowlink: usize) {
+        if lowlink < self.lowlinks[&node] {
+            self.lowlinks.insert(node, lowlink);
+        }
+    }
+
+    fn pop_component(&mut self, root: usize) {
+        let mut component = Vec::new();
+        while let Some(member) = self.stack.pop() {
+            self.on_stack.remove(&member);
+            component.push(member);
+            if member == root {
+                break;
+            }
+        }
+
+        self.components.push(component);
+    }
+}
+
+fn sort_components(mut components: Vec<Vec<usize>>) -> Vec<Vec<usize>> {
+    for component in &mut components {
+        component.sort();
+    }
+    components.sort();
+
+    components
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Topology {
+    // all nodes of the graph, nodes of a cycle are kept together in the order of indexes
+    pub order: Vec<usize>,
+    pub cycles: Vec<Vec<usize>>,
+}
+
+impl Topology {
+    pub fn is_acyclic(&self) -> bool {
+        self.cycles.is_empty()
+    }
+}
+
+pub fn topological_sort<T: Eq + Ord>(gh: &Graph<T>) -> Result<Vec<usize>, Vec<Vec<usize>>> {
+    let topology = topological_order(gh);
+    if topology.is_acyclic() {
+        Ok(topology.order)
+    } else {
+        Err(topology.cycles)
+    }
+}
 
-        for (i, neighbor) in edges.iter().enumerate() {
-            let tentative_g_score =  g_score[&current] + d(&neighbor.from.borrow(), i);
-            let neighbor_index = neighbor.to.borrow().index_in;
-            if tentative_g_score < g_score[&neighbor_index] {
-                came_from.entry(neighbor_index).and_modify(|n| *n = current).or_insert(current);
-                g_score.entry(neighbor_index).and_modify(|s| *s = tentative_g_score);
-                f_score.entry(neighbor_index).and_modify(|s| *s = tentative_g_score + h(neighbor_index));
-                
-                if !open_set.contains(&neighbor_index) {
-                    open_set.insert(neighbor_index);
+// Orders nodes topologically wherever it's possible.
+//
+// Strongly connected components are ordered by Kahn's algorithm, which prefers
+// components with smaller indexes, so independent nodes keep their original order.
+// A cycle is reported for each component which prevents a full ordering.
+pub fn topological_order<T: Eq + Ord>(gh: &Graph<T>) -> Topology {
+    let components = strongly_connected_components(gh);
+    let component_of = components.iter().enumerate()
+        .flat_map(|(i, component)| component.iter().map(move |&node| (node, i)))
+        .collect::<BTreeMap<usize, usize>>();
+
+    let mut successors = vec![BTreeSet::new(); components.len()];
+    let mut in_degree = vec![0; components.len()];
+    let mut cycles = Vec::new();
+    for (i, component) in components.iter().enumerate() {
+        for &node in component {
+            for (neighbor, _) in gh.neighbors(node) {
+                let j = component_of[&neighbor];
+                if i != j && successors[i].insert(j) {
+                    in_degree[j] += 1;
                 }
             }
         }
+
+        let self_linked = gh.neighbors(component[0]).iter().any(|&(n, _)| n == component[0]);
+        if component.len() > 1 || self_linked {
+            cycles.push(find_cycle(gh, component));
+        }
     }
 
-    None
+    // components are sorted, so their indexes follow the smallest nodes
+    let mut ready = (0..components.len()).filter(|&i| in_degree[i] == 0).map(Reverse).collect::<BinaryHeap<_>>();
+    let mut order = Vec::new();
+    while let Some(Reverse(i)) = ready.pop() {
+        order.extend(&components[i]);
+        for &j in &successors[i] {
+            in_degree[j] -= 1;
+            if in_degree[j] == 0 {
+                ready.push(Reverse(j));
+            }
+        }
+    }
+
+    Topology { order, cycles }
 }
 
-pub fn path(area: &BTreeMap<usize, usize>, from: usize, to: usize) -> Option<Vec<usize>> {
-    let mut i = area.get(&from);
-    if i.is_none() {
-        return None;
+// the shortest cycle through the smallest node of a strongly connected component
+fn find_cycle<T: Eq + Ord>(gh: &Graph<T>, component: &[usize]) -> Vec<usize> {
+    let start = component[0];
+    let members = component.iter().cloned().collect::<BTreeSet<usize>>();
+
+    let mut prev = BTreeMap::new();
+    let mut queue = std::collections::VecDeque::new();
+    queue.push_back(start);
+    while let Some(node) = queue.pop_front() {
+        for (neighbor, _) in gh.neighbors(node) {
+            if neighbor == start {
+                let mut cycle = vec![node];
+                while let Some(&p) = prev.get(cycle.last().unwrap()) {
+                    cycle.push(p);
+                }
+                cycle.reverse();
+
+                return cycle;
+            }
+
+            if members.contains(&neighbor) && !prev.contains_key(&neighbor) {
+                prev.insert(neighbor, node);
+                queue.push_back(neighbor);
+            }
+        }
     }
 
+    component.to_vec()
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Flow {
+    pub value: Weight,
+    // flow between pairs of nodes, parallel links are merged into one
+    pub flows: BTreeMap<(usize, usize), Weight>,
+    pub saturated: Vec<(usize, usize)>,
+    // links of a minimum cut, they go from the source side to the rest of the graph
+    pub cut: Vec<(usize, usize)>,
+    pub source_side: BTreeSet<usize>,
+}
+
+// Edmonds-Karp's maximum flow, weights of links are used as capacities.
+//
+// Negative weights are considered as zero capacities.
+pub fn max_flow<T: Eq + Ord>(gh: &Graph<T>, source: usize, sink: usize) -> Result<Flow, SearchError> {
+    check_endpoints(gh, source, sink)?;
+
+    let mut capacities: BTreeMap<(usize, usize), Weight> = BTreeMap::new();
+    let mut adjacent: BTreeMap<usize, BTreeSet<usize>> = BTreeMap::new();
+    for (from, to, link) in all_links(gh) {
+        *capacities.entry((from, to)).or_default() += std::cmp::max(link.weight, 0);
+        adjacent.entry(from).or_default().insert(to);
+        adjacent.entry(to).or_default().insert(from);
+    }
+
+    let mut residual = capacities.clone();
+    let residual_of = |residual: &BTreeMap<(usize, usize), Weight>, from: usize, to: usize| residual.get(&(from, to)).cloned().unwrap_or(0);
+
+    let mut value = 0;
+    let source_side = loop {
+        // breadth first search of the shortest augmenting path
+        let mut prev = BTreeMap::new();
+        let mut reached = BTreeSet::new();
+        let mut queue = std::collections::VecDeque::new();
+        reached.insert(source);
+        queue.push_back(source);
+        while let Some(node) = queue.pop_front() {
+            if node == sink {
+                break;
+            }
+
+            for &next in adjacent.get(&node).into_iter().flatten() {
+                if residual_of(&residual, node, next) > 0 && reached.insert(next) {
+                    prev.insert(next, node);
+                    queue.push_back(next);
+                }
+            }
+        }
+
+        if source == sink || !reached.contains(&sink) {
+            break reached;
+        }
+
+        let mut path = vec![sink];
+        while let Some(&p) = prev.get(path.last().unwrap()) {
+            path.push(p);
+        }
+        path.reverse();
+
+        let bottleneck = path.windows(2).map(|pair| residual_of(&residual, pair[0], pair[1])).min().unwrap_or(0);
+        for pair in path.windows(2) {
+            *residual.entry((pair[0], pair[1])).or_default() -= bottleneck;
+            *residual.entry((pair[1], pair[0])).or_default() += bottleneck;
+        }
+        value += bottleneck;
+    };
+
+    let mut flow = Flow { value, flows: BTreeMap::new(), saturated: Vec::new(), cut: Vec::new(), source_side };
+    for (&(from, to), &capacity) in &capacities {
+        let passed = capacity - residual_of(&residual, from, to);
+        if passed > 0 {
+            flow.flows.insert((from, to), passed);
+        }
+
+        if capacity > 0 && passed >= capacity {
+            flow.saturated.push((from, to));
+        }
+
+        if capacity > 0 && flow.source_side.contains(&from) && !flow.source_side.contains(&to) {
+            flow.cut.push((from, to));
+        }
+    }
+
+    Ok(flow)
+}
+
+// Yen's k shortest loopless paths, they're ordered by cost.
+pub fn k_shortest_paths<T: Eq + Ord>(gh: &Graph<T>, source: usize, target: usize, k: usize) -> Result<Vec<SearchResult>, SearchError> {
+    if k == 0 {
+        return Ok(Vec::new());
+    }
+
+    let mut found = vec![dijkstra(gh, source, target)?];
+    let mut candidates: BTreeSet<(Weight, Vec<usize>, BTreeSet<usize>)> = BTreeSet::new();
+
+    while found.len() < k {
+        let last = found.last().unwrap().path.clone();
+        for i in 0..last.len() - 1 {
+            let spur = last[i];
+            let root = &last[..=i];
+
+            let banned_links = found.iter()
+                .filter(|result| result.path.len() > i + 1 && &result.path[..=i] == root)
+                .map(|result| (result.path[i], result.path[i + 1]))
+                .collect::<BTreeSet<(usize, usize)>>();
+            let banned_nodes = root[..i].iter().cloned().collect::<BTreeSet<usize>>();
+
+            let links = |n: usize| -> Vec<(usize, Weight)> {
+                gh.neighbors(n).into_iter()
+                    .filter(|(to, _)| !banned_nodes.contains(to) && !banned_links.contains(&(n, *to)))
+                    .collect()
+            };
+            let spur_paths = match run_dijkstra(spur, Some(target), &links) {
+                (_, Ok(paths)) => paths,
+                (_, Err(err)) => return Err(err),
+            };
+            let spur_result = match spur_paths.search_result(spur, target) {
+                Ok(result) => result,
+                Err(_) => continue,
+            };
+
+            let root_cost = root.windows(2).map(|pair| link_weight(gh, pair[0], pair[1])).sum::<Weight>();
+            let mut path = root[..i].to_vec();
+            path.extend(spur_result.path);
+            candidates.insert((add_cost(root_cost, spur_result.cost)?, path, spur_result.visited));
+        }
+
+        let next = loop {
+            match candidates.pop_first() {
+                Some((cost, path, visited)) => {
+                    if found.iter().all(|result| result.path != path) {
+                        break Some(SearchResult { path, cost, visited });
+                    }
+                }
+                None => break None,
+            }
+        };
+
+        match next {
+            Some(result) => found.push(result),
+            None => break,
+        }
+    }
+
+    Ok(found)
+}
+
+// the lightest link between 2 nodes
+fn link_weight<T: Eq + Ord>(gh: &Graph<T>, from: usize, to: usize) -> Weight {
+    gh.neighbors(from).into_iter().filter(|&(n, _)| n == to).map(|(_, weight)| weight).min().unwrap_or(0)
+}
+
+pub fn path(area: &BTreeMap<usize, usize>, from: usize, to: usize) -> Result<Vec<usize>, SearchError> {
     let mut path = vec![from];
-    while i.is_some() {
-        let point = i.unwrap();
-        path.push(*point);
-        if *point == to {
-            break;
+    let mut point = from;
+    while point != to {
+        match area.get(&point) {
+            Some(&next) => point = next,
+            None => return Err(SearchError::Unreachable(to, from)),
+        }
+        path.push(point);
+    }
+
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dijkstra_prefers_lighter_path() {
+        let gh = example();
+        let (_, paths) = shortest_paths(&gh, 0, None);
+        let paths = paths.unwrap();
+
+        assert_eq!(paths.dist[&3], 3);
+        assert_eq!(paths.path_to(3), Some(vec![0, 1, 2, 3]));
+        assert_eq!(paths.path_to(4), Some(vec![0, 1, 2, 3, 4]));
+    }
+
+    #[test]
+    fn test_dijkstra_does_not_set_predecessor_of_source() {
+        let gh = example();
+        let (_, paths) = shortest_paths(&gh, 0, None);
+
+        assert!(!paths.unwrap().prev.contains_key(&0));
+    }
+
+    #[test]
+    fn test_dijkstra_stops_on_settled_goal() {
+        let gh = example();
+        let (steps, result) = dijkstra_extra(&gh, 0, 1);
+
+        assert_eq!(steps, vec![vec![0, 1, 3]]);
+        assert_eq!(result.unwrap().path, vec![0, 1]);
+    }
+
+    #[test]
+    fn test_dijkstra_result() {
+        let gh = example();
+        let result = dijkstra(&gh, 0, 4).unwrap();
+
+        assert_eq!(result.path, vec![0, 1, 2, 3, 4]);
+        assert_eq!(result.cost, 4);
+        assert!(result.visited.contains(&4));
+    }
+
+    #[test]
+    fn test_predecessors_path() {
+        let gh = example();
+        let (_, paths) = shortest_paths(&gh, 0, None);
+
+        assert_eq!(path(&paths.unwrap().prev, 4, 0), Ok(vec![4, 3, 2, 1, 0]));
+    }
+
+    #[test]
+    fn test_search_errors() {
+        let mut gh = example();
+        gh.add_node("leaf");
+
+        assert_eq!(dijkstra(&gh, 5, 5).unwrap().path, vec![5]);
+        assert_eq!(dijkstra(&gh, 5, 0), Err(SearchError::Unreachable(5, 0)));
+        gh.block(5);
+        assert_eq!(dijkstra(&gh, 5, 0), Err(SearchError::BlockedSource(5)));
+        assert_eq!(dijkstra(&gh, 42, 0), Err(SearchError::UnknownSource(42)));
+        assert_eq!(dijkstra(&gh, 0, 42), Err(SearchError::UnknownTarget(42)));
+        assert_eq!(dijkstra(&gh, 2, 0), Err(SearchError::Unreachable(2, 0)));
+        assert_eq!(a_star(&gh, 2, 0, |_| 0), Err(SearchError::Unreachable(2, 0)));
+    }
+
+    #[test]
+    fn test_blocked_node_cannot_be_entered() {
+        let mut gh = example();
+        gh.block(2);
+
+        assert_eq!(dijkstra(&gh, 0, 4).unwrap().path, vec![0, 3, 4]);
+        assert_eq!(dijkstra(&gh, 0, 2), Err(SearchError::Unreachable(0, 2)));
+        assert_eq!(dijkstra(&gh, 2, 4), Err(SearchError::BlockedSource(2)));
+        assert_eq!(bellman_ford(&gh, 0, 2), Err(SearchError::Unreachable(0, 2)));
+
+        gh.unblock(2);
+        assert_eq!(dijkstra(&gh, 0, 4).unwrap().path, vec![0, 1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn test_a_star_matches_dijkstra() {
+        let gh = example();
+        let result = a_star(&gh, 0, 4, |n| 4 - std::cmp::min(n, 4) as Weight).unwrap();
+
+        assert_eq!(result.path, vec![0, 1, 2, 3, 4]);
+        assert_eq!(result.cost, 4);
+    }
+
+    #[test]
+    fn test_negative_weights() {
+        let mut gh = example();
+        let f = gh.add_node("f");
+        gh.link(0, f, 2);
+        gh.link(f, 3, -5);
+
+        let result = bellman_ford(&gh, 0, 4).unwrap();
+        assert_eq!(result.path, vec![0, 5, 3, 4]);
+        assert_eq!(result.cost, -2);
+
+        assert_eq!(dijkstra(&gh, 0, 4), Err(SearchError::NegativeWeight(5, 3)));
+    }
+
+    #[test]
+    fn test_cost_overflow() {
+        let mut gh = Graph::new();
+        let nodes = (0..3).map(|_| gh.add_node("n")).collect::<Vec<usize>>();
+        gh.link(nodes[0], nodes[1], Weight::MAX);
+        gh.link(nodes[1], nodes[2], 1);
+
+        assert_eq!(dijkstra(&gh, 0, 2), Err(SearchError::CostOverflow));
+        assert_eq!(a_star(&gh, 0, 2, |_| Weight::MAX), Err(SearchError::CostOverflow));
+        assert_eq!(bidirectional_a_star(&gh, 0, 2, |_| 0, |_| 0), Err(SearchError::CostOverflow));
+        assert_eq!(bellman_ford(&gh, 0, 2), Err(SearchError::CostOverflow));
+        assert_eq!(floyd_warshall(&gh).err(), Some(SearchError::CostOverflow));
+        assert_eq!(k_shortest_paths(&gh, 0, 2, 2), Err(SearchError::CostOverflow));
+        assert_eq!(dijkstra(&gh, 0, 1).unwrap().cost, Weight::MAX);
+
+        gh.link(nodes[2], nodes[0], Weight::MIN);
+        assert_eq!(bellman_ford(&gh, 2, 1).unwrap().cost, -1);
+    }
+
+    #[test]
+    fn test_negative_cycle() {
+        let mut gh = example();
+        gh.link(2, 1, -2);
+
+        match bellman_ford(&gh, 0, 4) {
+            Err(SearchError::NegativeCycle(mut cycle)) => {
+                cycle.sort();
+                assert_eq!(cycle, vec![1, 2]);
+            }
+            result => panic!("unexpected result {:?}", result),
         }
-        i = area.get(&point);
     }
-            
-    Some(path)
-}
\ No newline at end of file
+
+    #[test]
+    fn test_floyd_warshall() {
+        let gh = example();
+        let table = floyd_warshall(&gh).unwrap();
+
+        assert_eq!(table.distance(0, 4), Some(4));
+        assert_eq!(table.distance(1, 0), Some(1));
+        assert_eq!(table.distance(4, 0), None);
+        assert_eq!(table.path(0, 4), Some(vec![0, 1, 2, 3, 4]));
+        assert_eq!(table.path(3, 3), Some(vec![3]));
+        assert_eq!(table.path(2, 0), None);
+
+        for from in 0..5 {
+            if let Ok(paths) = shortest_paths(&gh, from, None).1 {
+                for to in 0..5 {
+                    assert_eq!(table.distance(from, to), paths.dist.get(&to).cloned());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_floyd_warshall_negative_cycle() {
+        let mut gh = example();
+        gh.link(2, 1, -2);
+
+        assert!(matches!(floyd_warshall(&gh), Err(SearchError::NegativeCycle(_))));
+    }
+
+    #[test]
+    fn test_floyd_warshall_limit() {
+        let mut gh = Graph::new();
+        for _ in 0..ALL_PAIRS_LIMIT {
+            gh.add_node(0);
+        }
+        assert!(floyd_warshall(&gh).is_ok());
+
+        gh.add_node(0);
+        assert_eq!(floyd_warshall(&gh).err(), Some(SearchError::TooManyNodes(ALL_PAIRS_LIMIT + 1, ALL_PAIRS_LIMIT)));
+    }
+
+    #[test]
+    fn test_shortest_paths_to() {
+        let gh = example();
+        let paths = shortest_paths_to(&gh, 3).unwrap();
+
+        assert_eq!(paths.dist[&0], 3);
+        assert_eq!(paths.prev[&0], 1);
+        assert!(!paths.dist.contains_key(&4));
+    }
+
+    #[test]
+    fn test_bidirectional_search() {
+        let gh = example();
+        let result = bidirectional_a_star(&gh, 0, 4, |_| 0, |_| 0).unwrap();
+
+        assert_eq!(result.path, vec![0, 1, 2, 3, 4]);
+        assert_eq!(result.cost, 4);
+        assert_eq!(bidirectional_a_star(&gh, 3, 3, |_| 0, |_| 0).unwrap().path, vec![3]);
+        assert_eq!(bidirectional_a_star(&gh, 2, 0, |_| 0, |_| 0), Err(SearchError::Unreachable(2, 0)));
+    }
+
+    #[test]
+    fn test_bidirectional_search_on_grid() {
+        let (gh, w) = grid(7, 5);
+        let manhattan = |a: usize, b: usize| ((a % w) as Weight - (b % w) as Weight).abs() + ((a / w) as Weight - (b / w) as Weight).abs();
+
+        for &(from, to) in &[(0, 34), (34, 0), (3, 31), (10, 12)] {
+            let expected = dijkstra(&gh, from, to).unwrap();
+            let result = bidirectional_a_star(&gh, from, to, |n| manhattan(n, to), |n| manhattan(n, from)).unwrap();
+
+            assert_eq!(result.cost, expected.cost);
+            assert_eq!(result.path.first(), Some(&from));
+            assert_eq!(result.path.last(), Some(&to));
+            assert_eq!(result.path.len() as Weight, result.cost + 1);
+        }
+    }
+
+    #[test]
+    fn test_spanning_trees() {
+        let mut gh = example();
+        gh.link(4, 0, 2);
+
+        for tree in [prim(&gh), kruskal(&gh)] {
+            let mut pairs = tree.pairs();
+            pairs.sort();
+
+            assert_eq!(tree.weight, 4);
+            assert_eq!(pairs.len(), 4);
+            assert!(!pairs.contains(&(0, 3)));
+        }
+    }
+
+    #[test]
+    fn test_spanning_forest() {
+        let (mut gh, _) = grid(4, 3);
+        gh.add_node("isolated");
+
+        let kruskal = kruskal(&gh);
+        let prim = prim(&gh);
+
+        assert_eq!(prim.weight, 11);
+        assert_eq!(kruskal.weight, 11);
+        assert_eq!(prim.links.len(), 11);
+    }
+
+    #[test]
+    fn test_connected_components() {
+        let mut gh = example();
+        let f = gh.add_node("f");
+        let g = gh.add_node("g");
+        gh.link(g, f, 1);
+        gh.add_node("h");
+
+        assert_eq!(connected_components(&gh), vec![vec![0, 1, 2, 3, 4], vec![5, 6], vec![7]]);
+    }
+
+    #[test]
+    fn test_strongly_connected_components() {
+        let mut gh = example();
+        gh.link(3, 1, 1);
+
+        assert_eq!(strongly_connected_components(&gh), vec![vec![0, 1, 2, 3], vec![4]]);
+
+        let (gh, _) = grid(30, 30);
+        assert_eq!(strongly_connected_components(&gh).len(), 1);
+    }
+
+    #[test]
+    fn test_topological_sort() {
+        let gh = example();
+        assert!(topological_sort(&gh).is_err());
+
+        let mut gh = Graph::new();
+        let nodes = (0..5).map(|_| gh.add_node("node")).collect::<Vec<_>>();
+        for &(from, to) in &[(3, 0), (0, 1), (4, 1), (2, 1)] {
+            gh.link(nodes[from], nodes[to], 1);
+        }
+
+        assert_eq!(topological_sort(&gh), Ok(vec![2, 3, 0, 4, 1]));
+    }
+
+    #[test]
+    fn test_topological_order_with_cycles() {
+        let mut gh = example();
+        gh.link(4, 4, 1);
+        let topology = topological_order(&gh);
+
+        assert_eq!(topology.order, vec![0, 1, 2, 3, 4]);
+        assert_eq!(topology.cycles, vec![vec![0, 1], vec![4]]);
+        assert!(!topology.is_acyclic());
+    }
+
+    #[test]
+    fn test_max_flow() {
+        let mut gh = Graph::new();
+        let nodes = (0..6).map(|_| gh.add_node("node")).collect::<Vec<_>>();
+        let links = [(0, 1, 16), (0, 2, 13), (1, 2, 10), (2, 1, 4), (1, 3, 12), (3, 2, 9), (2, 4, 14), (4, 3, 7), (3, 5, 20), (4, 5, 4)];
+        for &(from, to, weight) in &links {
+            gh.link(nodes[from], nodes[to], weight);
+        }
+
+        let flow = max_flow(&gh, 0, 5).unwrap();
+        assert_eq!(flow.value, 23);
+        assert_eq!(flow.cut.iter().map(|pair| flow.flows[pair]).sum::<Weight>(), 23);
+        assert!(flow.cut.iter().all(|pair| flow.saturated.contains(pair)));
+
+        let (gh, _) = grid(4, 3);
+        assert_eq!(max_flow(&gh, 0, 11).unwrap().value, 2);
+        assert_eq!(max_flow(&gh, 0, 42), Err(SearchError::UnknownTarget(42)));
+    }
+
+    #[test]
+    fn test_k_shortest_paths() {
+        let mut gh = Graph::new();
+        let nodes = (0..6).map(|_| gh.add_node("node")).collect::<Vec<_>>();
+        let links = [(0, 1, 3), (0, 2, 2), (1, 3, 4), (2, 1, 1), (2, 3, 2), (2, 4, 3), (3, 4, 2), (3, 5, 1), (4, 5, 2)];
+        for &(from, to, weight) in &links {
+            gh.link(nodes[from], nodes[to], weight);
+        }
+
+        let paths = k_shortest_paths(&gh, 0, 5, 3).unwrap();
+        let costs = paths.iter().map(|result| result.cost).collect::<Vec<Weight>>();
+
+        assert_eq!(paths[0].path, vec![0, 2, 3, 5]);
+        assert_eq!(costs, vec![5, 7, 8]);
+        assert_eq!(k_shortest_paths(&gh, 0, 5, 100).unwrap().len(), 7);
+        assert!(k_shortest_paths(&gh, 0, 5, 0).unwrap().is_empty());
+        assert_eq!(k_shortest_paths(&gh, 5, 0, 2), Err(SearchError::Unreachable(5, 0)));
+        gh.block(5);
+        assert_eq!(k_shortest_paths(&gh, 5, 0, 2), Err(SearchError::BlockedSource(5)));
+    }
+
+    fn grid(w: usize, h: usize) -> (Graph<&'static str>, usize) {
+        let mut gh = Graph::new();
+        let nodes = (0..w * h).map(|_| gh.add_node("cell")).collect::<Vec<_>>();
+        for i in 0..w * h {
+            if i % w + 1 < w {
+                gh.link(nodes[i], nodes[i + 1], 1);
+                gh.link(nodes[i + 1], nodes[i], 1);
+            }
+            if i + w < w * h {
+                gh.link(nodes[i], nodes[i + w], 1);
+                gh.link(nodes[i + w], nodes[i], 1);
+            }
+        }
+
+        (gh, w)
+    }
+
+    fn example() -> Graph<&'static str> {
+        let mut gh = Graph::new();
+        let a = gh.add_node("a");
+        let b = gh.add_node("b");
+        let c = gh.add_node("c");
+        let d = gh.add_node("d");
+        let e = gh.add_node("e");
+
+        gh.link(a, b, 1);
+        gh.link(b, a, 1);
+        gh.link(a, d, 10);
+        gh.link(b, c, 1);
+        gh.link(c, d, 1);
+        gh.link(d, e, 1);
+
+        gh
+    }
+}
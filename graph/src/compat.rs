@@ -0,0 +1,203 @@
+// The graph API of shared node handles, which came before nodes were kept in arenas.
+// A handle refers to the arena it came from and to an index in it, nodes don't refer to
+// each other, so handles make no reference cycles.
+use std::cell::{Ref, RefCell, RefMut};
+use std::collections::{BTreeMap, BTreeSet};
+use std::rc::Rc;
+
+use crate::node::{self, Node, NodeId, Weight};
+
+pub struct NodeRef<T: Eq + Ord> {
+    gh: Rc<RefCell<node::Graph<T>>>,
+    index: NodeId,
+}
+
+// derive would require T to be Clone, which isn't necessary to clone handles
+impl<T: Eq + Ord> Clone for NodeRef<T> {
+    fn clone(&self) -> Self {
+        NodeRef {
+            gh: self.gh.clone(),
+            index: self.index,
+        }
+    }
+}
+
+impl<T: Eq + Ord> NodeRef<T> {
+    pub fn index(&self) -> NodeId {
+        self.index
+    }
+
+    pub fn borrow(&self) -> Ref<'_, Node<T>> {
+        Ref::map(self.gh.borrow(), |gh| gh.node_by_index(self.index).expect("a handle of a removed node"))
+    }
+
+    pub fn borrow_mut(&self) -> RefMut<'_, Node<T>> {
+        RefMut::map(self.gh.borrow_mut(), |gh| gh.node_by_index_mut(self.index).expect("a handle of a removed node"))
+    }
+
+    // nodes reachable from this one are visited after the ones they lead to, each of them once
+    pub fn walk<F>(&self, f: &mut F) where F: FnMut(&Node<T>) {
+        let gh = self.gh.borrow();
+        let mut visited = BTreeSet::new();
+        let mut stack = vec![(self.index, gh.neighbors(self.index).into_iter())];
+        visited.insert(self.index);
+        while let Some((index, neighbors)) = stack.last_mut() {
+            let index = *index;
+            match neighbors.find(|(next, _)| !visited.contains(next)) {
+                Some((next, _)) => {
+                    visited.insert(next);
+                    stack.push((next, gh.neighbors(next).into_iter()));
+                },
+                None => {
+                    if let Some(node) = gh.node_by_index(index) {
+                        f(node);
+                    }
+                    stack.pop();
+                },
+            }
+        }
+    }
+}
+
+pub struct Graph<T: Eq + Ord> {
+    pub root: Option<NodeRef<T>>,
+    gh: Rc<RefCell<node::Graph<T>>>,
+}
+
+impl<T: Eq + Ord> Default for Graph<T> {
+    fn default() -> Self {
+        Graph::new()
+    }
+}
+
+impl<T: Eq + Ord> From<node::Graph<T>> for Graph<T> {
+    fn from(gh: node::Graph<T>) -> Self {
+        let root = gh.root;
+        let gh = Rc::new(RefCell::new(gh));
+        Graph {
+            root: root.map(|index| NodeRef { gh: gh.clone(), index }),
+            gh,
+        }
+    }
+}
+
+impl<T: Eq + Ord> Graph<T> {
+    pub fn new() -> Self {
+        Graph::from(node::Graph::new())
+    }
+
+    // the graph behind handles, algorithms take it
+    pub fn arena(&self) -> Ref<'_, node::Graph<T>> {
+        self.gh.borrow()
+    }
+
+    pub fn add_node(&mut self, d: T) -> NodeRef<T> {
+        let index = self.gh.borrow_mut().add_node(d);
+        let node = NodeRef { gh: self.gh.clone(), index };
+        if self.root.is_none() {
+            self.root = Some(node.clone());
+        }
+
+        node
+    }
+
+    pub fn node_by_index(&self, i: NodeId) -> Option<NodeRef<T>> {
+        self.gh.borrow().node_by_index(i)?;
+        Some(NodeRef { gh: self.gh.clone(), index: i })
+    }
+
+    pub fn neighbors(&self, i: NodeId) -> Vec<(NodeId, Weight)> {
+        self.gh.borrow().neighbors(i)
+    }
+
+    pub fn area(&self) -> BTreeMap<NodeId, NodeRef<T>> {
+        let indices = self.gh.borrow().indices().collect::<Vec<NodeId>>();
+        indices.into_iter().map(|index| (index, NodeRef { gh: self.gh.clone(), index })).collect()
+    }
+
+    pub fn link(left: NodeRef<T>, right: NodeRef<T>, w: Weight) {
+        assert!(Rc::ptr_eq(&left.gh, &right.gh), "a link between nodes of different graphs");
+        left.gh.borrow_mut().link(left.index, right.index, w);
+    }
+
+    pub fn count(&self) -> i64 {
+        match &self.root {
+            Some(root) => Graph::go(root.clone()).0,
+            None => 0,
+        }
+    }
+
+    pub fn deep(&self) -> i64 {
+        match &self.root {
+            Some(root) => Graph::go(root.clone()).1,
+            None => 0,
+        }
+    }
+
+    // nodes reachable from the node and the longest path from it
+    pub fn go(n: NodeRef<T>) -> (i64, i64) {
+        let gh = n.gh.borrow();
+        (gh.bfs(n.index).count() as i64, gh.longest_path(n.index) as i64)
+    }
+
+    pub fn for_each<F: FnMut(RefMut<Node<T>>)>(&mut self, mut f: F) {
+        for (_, node) in self.area() {
+            f(node.borrow_mut());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_handles() {
+        let mut graph = Graph::new();
+        let a = graph.add_node("a");
+        let b = graph.add_node("b");
+        let c = graph.add_node("c");
+        Graph::link(a.clone(), b.clone(), 1);
+        Graph::link(b.clone(), c.clone(), 2);
+        Graph::link(c.clone(), a.clone(), 3);
+
+        assert_eq!(graph.root.as_ref().map(NodeRef::index), Some(a.index()));
+        assert_eq!(graph.neighbors(b.index()), vec![(c.index(), 2)]);
+        assert_eq!(graph.area().keys().cloned().collect::<Vec<NodeId>>(), vec![0, 1, 2]);
+        assert!(graph.node_by_index(3).is_none());
+
+        b.borrow_mut().data = "B";
+        assert_eq!(graph.node_by_index(1).unwrap().borrow().data, "B");
+        assert_eq!((graph.count(), graph.deep()), (3, 3));
+        assert_eq!(Graph::go(c.clone()), (3, 3));
+
+        let mut walked = Vec::new();
+        a.walk(&mut |node| walked.push(node.data));
+        assert_eq!(walked, vec!["c", "B", "a"]);
+
+        graph.for_each(|mut node| node.data = if node.data == "B" { "b" } else { node.data });
+        assert_eq!(b.borrow().data, "b");
+        assert_eq!(graph.arena().len(), 3);
+    }
+
+    #[test]
+    fn test_from_arena() {
+        let mut arena = node::Graph::new();
+        let a = arena.add_node(1);
+        let b = arena.add_node(2);
+        arena.link(a, b, 5);
+
+        let graph = Graph::from(arena);
+        assert_eq!(graph.root.as_ref().map(NodeRef::index), Some(a));
+        assert_eq!(graph.neighbors(a), vec![(b, 5)]);
+        assert_eq!(Graph::<i32>::default().count(), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "a link between nodes of different graphs")]
+    fn test_link_between_graphs() {
+        let mut first = Graph::new();
+        let mut second = Graph::new();
+        Graph::link(first.add_node(1), second.add_node(2), 1);
+    }
+}
@@ -1,7 +1,10 @@
 mod node;
+mod traversal;
 pub mod algorithm;
+pub mod compat;
 
-pub use node::{Node, Graph};
+pub use node::{Node, Graph, Link, Weight, NodeId, EdgeId};
+pub use traversal::{Bfs, Dfs, Traversal};
 
 #[cfg(test)]
 mod tests {
@@ -1,15 +1,22 @@
 
 #![allow(unused)]
 use serde::{Serialize, Deserialize};
-use std::rc::Rc;
-use std::cell::RefCell;
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
+
+pub type Weight = i64;
+
+// nodes and links live in contiguous vectors of a graph and refer to each other by index,
+// so there are no reference cycles and nothing to borrow at runtime
+pub type NodeId = usize;
+pub type EdgeId = usize;
 
 #[derive(Debug, Eq)]
 pub struct Node<T: Eq + Ord> {
     pub data: T,
-    pub edges: Option<Vec<Link<T>>>,
-    pub index_in: usize,
+    pub edges: Option<Vec<EdgeId>>,
+    pub index_in: NodeId,
+    // a blocked node keeps its links but none of them can be walked through
+    pub blocked: bool,
 }
 
 impl<T: Eq + Ord> Ord for Node<T> {
@@ -26,18 +33,15 @@ impl<T: Eq + Ord> PartialOrd for Node<T> {
 
 impl<T: Eq + Ord> PartialEq for Node<T> {
     fn eq(&self, other: &Self) -> bool {
-        match self.cmp(&other) {
-            std::cmp::Ordering::Equal => true,
-            _=> false,
-        }
+        self.cmp(other) == std::cmp::Ordering::Equal
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Ord, PartialOrd)]
-pub struct Link<T: Eq + Ord> {
-    pub weight: usize,
-    pub from: Rc<RefCell<Node<T>>>,
-    pub to: Rc<RefCell<Node<T>>>,
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Ord, PartialOrd, Serialize, Deserialize)]
+pub struct Link {
+    pub weight: Weight,
+    pub from: NodeId,
+    pub to: NodeId,
 }
 
 impl<T: Eq + Ord> Node<T> {
@@ -46,104 +50,296 @@ impl<T: Eq + Ord> Node<T> {
             data: s,
             edges: None,
             index_in: 0,
+            blocked: false,
         }
     }
     
     pub fn is_leaf(&self) -> bool {
         self.edges.is_none()
     }
-    
-    pub fn walk<F>(&self, f: &mut F) where F: FnMut(&Node<T>) {
-        match &self.edges {
-            Some(children) => {
-                for child in children {
-                    child.to.borrow().walk(f);
-                }
-            },
-            _ => (),
-        }
-        
-        f(self);
-    }
 }
 
+// removed nodes and links leave empty slots behind, so indexes given out once never change
 pub struct Graph<T: Eq + Ord> {
-    pub root: Option<Rc<RefCell<Node<T>>>>,
-    pub area: BTreeMap<usize, Rc<RefCell<Node<T>>>>,
-    index: usize,
+    pub root: Option<NodeId>,
+    nodes: Vec<Option<Node<T>>>,
+    links: Vec<Option<Link>>,
+}
+
+impl<T: Eq + Ord> Default for Graph<T> {
+    fn default() -> Self {
+        Graph::new()
+    }
 }
 
 impl<T: Eq + Ord> Graph<T> {
     pub fn new() -> Self {
         Graph{
             root: None,
-            area: BTreeMap::new(),
-            index: 0,
+            nodes: Vec::new(),
+            links: Vec::new(),
         }
     }
 
-    pub fn add_node(&mut self, d: T) -> Rc<RefCell<Node<T>>> {
+    pub fn add_node(&mut self, d: T) -> NodeId {
+        let index = self.nodes.len();
         let mut node = Node::new(d);
-        node.index_in = self.index;
+        node.index_in = index;
+        self.nodes.push(Some(node));
 
-        let s = Rc::new(RefCell::new(node));
-        self.area.insert(self.index, s.clone());
+        if self.root.is_none() {
+            self.root = Some(index);
+        }
 
-        self.index += 1;
+        index
+    }
 
-        if self.root.is_none() {
-            self.root = Some(s.clone());
+    pub fn node_by_index(&self, i: NodeId) -> Option<&Node<T>> {
+        self.nodes.get(i).and_then(Option::as_ref)
+    }
+
+    pub fn node_by_index_mut(&mut self, i: NodeId) -> Option<&mut Node<T>> {
+        self.nodes.get_mut(i).and_then(Option::as_mut)
+    }
+
+    pub fn link_by_index(&self, e: EdgeId) -> Option<&Link> {
+        self.links.get(e).and_then(Option::as_ref)
+    }
+
+    pub fn link_by_index_mut(&mut self, e: EdgeId) -> Option<&mut Link> {
+        self.links.get_mut(e).and_then(Option::as_mut)
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes().count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes().next().is_none()
+    }
+
+    pub fn indices(&self) -> impl Iterator<Item = NodeId> + '_ {
+        self.nodes().map(|node| node.index_in)
+    }
+
+    pub fn nodes(&self) -> impl Iterator<Item = &Node<T>> {
+        self.nodes.iter().flatten()
+    }
+
+    pub fn is_blocked(&self, i: NodeId) -> bool {
+        self.node_by_index(i).is_some_and(|node| node.blocked)
+    }
+
+    // outgoing links of a node in the order they were added, links of blocked nodes are skipped
+    pub fn links_from(&self, i: NodeId) -> impl Iterator<Item = &Link> {
+        let edges = self.node_by_index(i).filter(|node| !node.blocked).and_then(|node| node.edges.as_ref());
+        edges.into_iter().flatten()
+            .filter_map(move |&e| self.link_by_index(e))
+            .filter(move |link| !self.is_blocked(link.to))
+    }
+
+    pub fn neighbors(&self, i: NodeId) -> Vec<(NodeId, Weight)> {
+        self.links_from(i).map(|link| (link.to, link.weight)).collect()
+    }
+
+    pub fn link(&mut self, left: NodeId, right: NodeId, w: Weight) -> EdgeId {
+        let index = self.links.len();
+        self.links.push(Some(Link{from: left, to: right, weight: w}));
+        let node = self.node_by_index_mut(left).expect("a link from an unknown node");
+        match &mut node.edges {
+            Some(edges) => edges.push(index),
+            None => node.edges = Some(vec![index]),
         }
 
-        s
+        index
     }
 
-    pub fn node_by_index(&self, i: usize) -> Option<Rc<RefCell<Node<T>>>> {
-        match self.area.get(&i) {
-            Some(node) => Some(node.clone()),
-            _ => None,
+    pub fn remove_link(&mut self, e: EdgeId) -> Option<Link> {
+        let link = self.links.get_mut(e)?.take()?;
+        if let Some(edges) = self.node_by_index_mut(link.from).and_then(|node| node.edges.as_mut()) {
+            edges.retain(|&edge| edge != e);
         }
+
+        Some(link)
+    }
+
+    // removes every link which goes from a to b, returns how many of them there were
+    pub fn unlink(&mut self, a: NodeId, b: NodeId) -> usize {
+        let edges = match self.node_by_index(a).and_then(|node| node.edges.as_ref()) {
+            Some(edges) => edges.clone(),
+            None => return 0,
+        };
+
+        let links = &self.links;
+        let between = edges.into_iter()
+            .filter(|&e| links[e].is_some_and(|link| link.to == b))
+            .collect::<Vec<EdgeId>>();
+        for &e in &between {
+            self.remove_link(e);
+        }
+
+        between.len()
+    }
+
+    // removes the node together with its outgoing and incoming links
+    pub fn remove_node(&mut self, i: NodeId) -> Option<T> {
+        let node = self.nodes.get_mut(i)?.take()?;
+        for e in node.edges.into_iter().flatten() {
+            self.links[e] = None;
+        }
+
+        for e in 0..self.links.len() {
+            if self.links[e].is_some_and(|link| link.to == i) {
+                self.remove_link(e);
+            }
+        }
+
+        if self.root == Some(i) {
+            let root = self.indices().next();
+            self.root = root;
+        }
+
+        Some(node.data)
+    }
+
+    // a blocked node can't be entered or left by any search, though its links are kept to unblock it later
+    pub fn block(&mut self, i: NodeId) -> bool {
+        self.set_blocked(i, true)
+    }
+
+    pub fn unblock(&mut self, i: NodeId) -> bool {
+        self.set_blocked(i, false)
     }
 
-    pub fn link(left: Rc<RefCell<Node<T>>>, right: Rc<RefCell<Node<T>>>, w: usize) {
-        let link = Link{from: left.clone(), to: right, weight: w};
-        let mut ls = left.borrow_mut();
-        match &mut ls.edges {
-            Some(edges) => edges.push(link),
-            None => ls.edges = Some(vec![link]),
+    fn set_blocked(&mut self, i: NodeId, blocked: bool) -> bool {
+        match self.node_by_index_mut(i) {
+            Some(node) => {
+                node.blocked = blocked;
+                true
+            },
+            None => false,
         }
     }
 
     pub fn count(&self) -> i64 {
-        let(count, _) = Graph::go(self.root.clone().unwrap());
-        count
+        match self.root {
+            Some(root) => self.bfs(root).count() as i64,
+            None => 0,
+        }
     }
 
+    // nodes on the longest path from the root, links back to a node of the path are skipped so cycles end
     pub fn deep(&self) -> i64 {
-        let(_, d) = Graph::go(self.root.clone().unwrap());
-        d
-    }
-
-    pub fn go(n: Rc<RefCell<Node<T>>>) -> (i64, i64) {
-        let (mut count, mut deep) = (1, 1);
-        if let Some(children) = &n.borrow().edges {
-            let mut deep_leafs = Vec::with_capacity(children.len());
-            for child in children {
-                let (lc, ld) = Graph::go(child.to.clone());
-                count += lc;
-                deep_leafs.push(ld);
+        match self.root {
+            Some(root) => self.longest_path(root) as i64,
+            None => 0,
+        }
+    }
+
+    pub(crate) fn longest_path(&self, start: NodeId) -> usize {
+        let mut longest: BTreeMap<NodeId, usize> = BTreeMap::new();
+        let mut on_path = BTreeSet::new();
+        let mut stack = vec![(start, self.neighbors(start).into_iter())];
+        on_path.insert(start);
+        while let Some((node, neighbors)) = stack.last_mut() {
+            let node = *node;
+            match neighbors.find(|(next, _)| !on_path.contains(next) && !longest.contains_key(next)) {
+                Some((next, _)) => {
+                    on_path.insert(next);
+                    stack.push((next, self.neighbors(next).into_iter()));
+                },
+                None => {
+                    // every link which doesn't go back along the path leads to a finished node now
+                    let deepest = self.neighbors(node).iter().filter_map(|(next, _)| longest.get(next)).max().cloned().unwrap_or(0);
+                    longest.insert(node, deepest + 1);
+                    on_path.remove(&node);
+                    stack.pop();
+                },
             }
-            deep += deep_leafs.iter().max().unwrap();
         }
-        
-        (count, deep)
+
+        longest.get(&start).cloned().unwrap_or(0)
+    }
+
+    pub fn for_each<F: FnMut(&mut Node<T>)>(&mut self, f: F) {
+        self.nodes.iter_mut().flatten().for_each(f);
+    }
+
+    pub fn to_json(&self) -> serde_json::Result<String> where T: Serialize {
+        serde_json::to_string_pretty(self)
+    }
+
+    pub fn from_json<'de>(s: &'de str) -> serde_json::Result<Self> where T: Deserialize<'de> {
+        serde_json::from_str(s)
+    }
+}
+
+// JSON layout of a graph, links are listed node by node in the order they go out of it
+#[derive(Serialize, Deserialize)]
+struct GraphData<T> {
+    nodes: Vec<NodeData<T>>,
+    links: Vec<Link>,
+}
+
+// removed nodes leave gaps, so indices of a saved graph may exceed the number of its nodes,
+// but not this limit
+const INDEX_LIMIT: NodeId = 1 << 20;
+
+#[derive(Serialize, Deserialize)]
+struct NodeData<T> {
+    index: NodeId,
+    data: T,
+    #[serde(default)]
+    blocked: bool,
+}
+
+impl<T: Eq + Ord + Serialize> Serialize for Graph<T> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let nodes = self.nodes()
+            .map(|node| NodeData { index: node.index_in, data: &node.data, blocked: node.blocked })
+            .collect();
+        let links = self.nodes()
+            .flat_map(|node| node.edges.iter().flatten())
+            .filter_map(|&e| self.link_by_index(e).cloned())
+            .collect();
+
+        GraphData { nodes, links }.serialize(serializer)
     }
+}
+
+impl<'de, T: Eq + Ord + Deserialize<'de>> Deserialize<'de> for Graph<T> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::Error;
+
+        let data = GraphData::<T>::deserialize(deserializer)?;
+        let mut slots = Vec::new();
+        for node in data.nodes {
+            let len = node.index.checked_add(1).filter(|&len| len <= INDEX_LIMIT)
+                .ok_or_else(|| D::Error::custom(format!("node index {} is too large", node.index)))?;
+            if len > slots.len() {
+                slots.resize_with(len, || None);
+            }
+            if slots[node.index].is_some() {
+                return Err(D::Error::custom(format!("node {} is duplicated", node.index)));
+            }
 
-    pub fn for_each<F: FnMut(std::cell::RefMut<Node<T>>)>(&mut self, mut f: F) {
-        for v in self.area.values() {
-            let mut dd = v.borrow_mut();
-            f(dd);
+            let mut n = Node::new(node.data);
+            n.index_in = node.index;
+            n.blocked = node.blocked;
+            slots[node.index] = Some(n);
         }
+
+        let root = slots.iter().flatten().map(|node| node.index_in).next();
+        let mut gh = Graph { root, nodes: slots, links: Vec::new() };
+        for link in data.links {
+            if gh.node_by_index(link.from).is_none() || gh.node_by_index(link.to).is_none() {
+                return Err(D::Error::custom(format!("link {} -> {} refers to an unknown node", link.from, link.to)));
+            }
+
+            gh.link(link.from, link.to, link.weight);
+        }
+
+        Ok(gh)
     }
 }
 
@@ -158,7 +354,7 @@ mod tests {
         assert!(root.is_leaf());
         
         root.edges = Some(Vec::new());
-        assert_eq!(root.is_leaf(), false);
+        assert!(!root.is_leaf());
     }
 
     #[test]
@@ -184,6 +380,180 @@ mod tests {
         assert_eq!(tree.count(), 6);
     }
     
+    #[test]
+    fn test_deep_and_count_in_cyclic_graph() {
+        let mut graph = Graph::new();
+        let a = graph.add_node("a");
+        let b = graph.add_node("b");
+        let c = graph.add_node("c");
+
+        graph.link(a, b, 1);
+        graph.link(b, a, 1);
+        graph.link(b, c, 1);
+        graph.link(c, a, 1);
+
+        assert_eq!(graph.count(), 3);
+        assert_eq!(graph.deep(), 3);
+    }
+
+    #[test]
+    fn test_deep_is_the_longest_path() {
+        // a breadth-first tree would put c right under a
+        let mut graph = Graph::new();
+        let a = graph.add_node("a");
+        let b = graph.add_node("b");
+        let c = graph.add_node("c");
+        let d = graph.add_node("d");
+
+        graph.link(a, b, 1);
+        graph.link(a, c, 1);
+        graph.link(b, c, 1);
+        graph.link(c, d, 1);
+
+        assert_eq!(graph.deep(), 4);
+        assert_eq!(graph.bfs(a).traversal().depth(), 3);
+    }
+
+    #[test]
+    fn test_links_are_stored_by_index() {
+        let mut graph = Graph::new();
+        let a = graph.add_node("a");
+        let b = graph.add_node("b");
+
+        let ab = graph.link(a, b, 3);
+        let ba = graph.link(b, a, 4);
+
+        assert_eq!((a, b), (0, 1));
+        assert_eq!((ab, ba), (0, 1));
+        assert_eq!(graph.node_by_index(a).unwrap().edges, Some(vec![ab]));
+        assert_eq!(graph.link_by_index(ba), Some(&Link { weight: 4, from: b, to: a }));
+        assert_eq!(graph.neighbors(b), vec![(a, 4)]);
+
+        graph.link_by_index_mut(ab).unwrap().weight = 7;
+        assert_eq!(graph.neighbors(a), vec![(b, 7)]);
+    }
+
+    #[test]
+    fn test_remove_link_and_unlink() {
+        let mut graph = Graph::new();
+        let a = graph.add_node("a");
+        let b = graph.add_node("b");
+        let c = graph.add_node("c");
+
+        let ab = graph.link(a, b, 1);
+        graph.link(a, b, 2);
+        graph.link(a, c, 3);
+        graph.link(b, c, 4);
+
+        assert_eq!(graph.remove_link(ab), Some(Link { weight: 1, from: a, to: b }));
+        assert_eq!(graph.remove_link(ab), None);
+        assert_eq!(graph.neighbors(a), vec![(b, 2), (c, 3)]);
+
+        assert_eq!(graph.unlink(a, b), 1);
+        assert_eq!(graph.unlink(a, b), 0);
+        assert_eq!(graph.neighbors(a), vec![(c, 3)]);
+        assert_eq!(graph.neighbors(b), vec![(c, 4)]);
+    }
+
+    #[test]
+    fn test_remove_node_keeps_indexes() {
+        let mut graph = Graph::new();
+        let a = graph.add_node("a");
+        let b = graph.add_node("b");
+        let c = graph.add_node("c");
+        graph.link(a, b, 1);
+        graph.link(b, a, 1);
+        graph.link(b, c, 1);
+        graph.link(c, a, 1);
+
+        assert_eq!(graph.remove_node(a), Some("a"));
+        assert_eq!(graph.remove_node(a), None);
+        assert_eq!(graph.root, Some(b));
+        assert_eq!(graph.len(), 2);
+        assert_eq!(graph.indices().collect::<Vec<_>>(), vec![b, c]);
+        assert_eq!(graph.neighbors(b), vec![(c, 1)]);
+        assert!(graph.neighbors(c).is_empty());
+
+        let d = graph.add_node("d");
+        assert_eq!(d, 3);
+        assert_eq!(graph.node_by_index(c).unwrap().data, "c");
+    }
+
+    #[test]
+    fn test_block_and_unblock() {
+        let mut graph = Graph::new();
+        let a = graph.add_node("a");
+        let b = graph.add_node("b");
+        let c = graph.add_node("c");
+        graph.link(a, b, 1);
+        graph.link(b, c, 1);
+        graph.link(a, c, 5);
+
+        assert!(graph.block(b));
+        assert!(!graph.block(10));
+        assert_eq!(graph.neighbors(a), vec![(c, 5)]);
+        assert!(graph.neighbors(b).is_empty());
+        assert_eq!(graph.count(), 2);
+
+        assert!(graph.unblock(b));
+        assert_eq!(graph.neighbors(a), vec![(b, 1), (c, 5)]);
+        assert_eq!(graph.count(), 3);
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        let mut graph = example();
+        graph.remove_node(1);
+        graph.block(3);
+
+        let json = graph.to_json().unwrap();
+        let restored: Graph<String> = Graph::from_json(&json).unwrap();
+
+        assert_eq!(restored.indices().collect::<Vec<_>>(), vec![0, 2, 3, 4, 5]);
+        assert_eq!(restored.node_by_index(2).unwrap().data, "1234");
+        assert!(restored.is_blocked(3));
+        assert_eq!(restored.neighbors(0), vec![(2, 20)]);
+        assert_eq!(restored.to_json().unwrap(), json);
+    }
+
+    #[test]
+    fn test_json_layout() {
+        let mut graph = Graph::new();
+        let a = graph.add_node("a");
+        let b = graph.add_node("b");
+        graph.link(a, b, 5);
+
+        let json: serde_json::Value = serde_json::from_str(&graph.to_json().unwrap()).unwrap();
+        let expected = serde_json::json!({
+            "nodes": [
+                {"index": 0, "data": "a", "blocked": false},
+                {"index": 1, "data": "b", "blocked": false},
+            ],
+            "links": [{"weight": 5, "from": 0, "to": 1}],
+        });
+
+        assert_eq!(json, expected);
+    }
+
+    #[test]
+    fn test_json_rejects_unknown_nodes() {
+        let json = r#"{"nodes": [{"index": 0, "data": "a"}], "links": [{"weight": 1, "from": 0, "to": 7}]}"#;
+
+        assert!(Graph::<String>::from_json(json).is_err());
+    }
+
+    #[test]
+    fn test_json_rejects_huge_indexes() {
+        for index in &[INDEX_LIMIT, usize::MAX] {
+            let json = format!(r#"{{"nodes": [{{"index": {}, "data": "a"}}], "links": []}}"#, index);
+            let err = Graph::<String>::from_json(&json).err().unwrap();
+            assert!(err.to_string().starts_with(&format!("node index {} is too large", index)));
+        }
+
+        let json = format!(r#"{{"nodes": [{{"index": {}, "data": "a"}}], "links": []}}"#, INDEX_LIMIT - 1);
+        assert!(Graph::<String>::from_json(&json).unwrap().node_by_index(INDEX_LIMIT - 1).is_some());
+    }
+
     fn example() -> Graph<&'static str> {
         let mut graph = Graph::new();
         let a = graph.add_node("d: T");
@@ -193,11 +563,11 @@ mod tests {
         let w = graph.add_node("t");
         let q = graph.add_node("a");
         
-        Graph::link(a.clone(), b.clone(), 10);
-        Graph::link(a.clone(), c.clone(), 20);
-        Graph::link(c.clone(), d.clone(), 30);
-        Graph::link(d.clone(), w.clone(), 1);
-        Graph::link(d.clone(), q.clone(), 12);
+        graph.link(a, b, 10);
+        graph.link(a, c, 20);
+        graph.link(c, d, 30);
+        graph.link(d, w, 1);
+        graph.link(d, q, 12);
        
    
         graph
@@ -0,0 +1,189 @@
+use crate::node::Graph;
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Traversal {
+    pub order: Vec<usize>,
+    // discovery tree, a node is mapped to the one it was discovered from
+    pub parents: BTreeMap<usize, usize>,
+}
+
+impl Traversal {
+    pub fn depth(&self) -> usize {
+        let mut levels = BTreeMap::new();
+        for node in &self.order {
+            let level = match self.parents.get(node) {
+                Some(parent) => levels[parent] + 1,
+                None => 1,
+            };
+            levels.insert(*node, level);
+        }
+
+        levels.values().max().cloned().unwrap_or(0)
+    }
+}
+
+pub struct Bfs<'a, T: Eq + Ord> {
+    gh: &'a Graph<T>,
+    queue: VecDeque<usize>,
+    discovered: BTreeSet<usize>,
+    parents: BTreeMap<usize, usize>,
+}
+
+impl<'a, T: Eq + Ord> Bfs<'a, T> {
+    pub fn new(gh: &'a Graph<T>, start: usize) -> Self {
+        let mut bfs = Bfs {
+            gh,
+            queue: VecDeque::new(),
+            discovered: BTreeSet::new(),
+            parents: BTreeMap::new(),
+        };
+
+        if gh.node_by_index(start).is_some() {
+            bfs.queue.push_back(start);
+            bfs.discovered.insert(start);
+        }
+
+        bfs
+    }
+
+    pub fn parents(&self) -> &BTreeMap<usize, usize> {
+        &self.parents
+    }
+
+    pub fn traversal(mut self) -> Traversal {
+        let order = self.by_ref().collect();
+        Traversal { order, parents: self.parents }
+    }
+}
+
+impl<'a, T: Eq + Ord> Iterator for Bfs<'a, T> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        let node = self.queue.pop_front()?;
+        for (neighbor, _) in self.gh.neighbors(node) {
+            if self.discovered.insert(neighbor) {
+                self.parents.insert(neighbor, node);
+                self.queue.push_back(neighbor);
+            }
+        }
+
+        Some(node)
+    }
+}
+
+pub struct Dfs<'a, T: Eq + Ord> {
+    gh: &'a Graph<T>,
+    stack: Vec<(usize, Option<usize>)>,
+    visited: BTreeSet<usize>,
+    parents: BTreeMap<usize, usize>,
+}
+
+impl<'a, T: Eq + Ord> Dfs<'a, T> {
+    pub fn new(gh: &'a Graph<T>, start: usize) -> Self {
+        let mut stack = Vec::new();
+        if gh.node_by_index(start).is_some() {
+            stack.push((start, None));
+        }
+
+        Dfs {
+            gh,
+            stack,
+            visited: BTreeSet::new(),
+            parents: BTreeMap::new(),
+        }
+    }
+
+    pub fn parents(&self) -> &BTreeMap<usize, usize> {
+        &self.parents
+    }
+
+    pub fn traversal(mut self) -> Traversal {
+        let order = self.by_ref().collect();
+        Traversal { order, parents: self.parents }
+    }
+}
+
+impl<'a, T: Eq + Ord> Iterator for Dfs<'a, T> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        while let Some((node, parent)) = self.stack.pop() {
+            if !self.visited.insert(node) {
+                continue;
+            }
+
+            if let Some(parent) = parent {
+                self.parents.insert(node, parent);
+            }
+
+            // reversed so neighbors are visited in the order of links
+            for (neighbor, _) in self.gh.neighbors(node).into_iter().rev() {
+                if !self.visited.contains(&neighbor) {
+                    self.stack.push((neighbor, Some(node)));
+                }
+            }
+
+            return Some(node);
+        }
+
+        None
+    }
+}
+
+impl<T: Eq + Ord> Graph<T> {
+    pub fn bfs(&self, start: usize) -> Bfs<'_, T> {
+        Bfs::new(self, start)
+    }
+
+    pub fn dfs(&self, start: usize) -> Dfs<'_, T> {
+        Dfs::new(self, start)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bfs_order() {
+        let gh = example();
+        let traversal = gh.bfs(0).traversal();
+
+        assert_eq!(traversal.order, vec![0, 1, 2, 3, 4]);
+        assert_eq!(traversal.parents[&3], 1);
+        assert_eq!(traversal.parents[&4], 2);
+        assert_eq!(traversal.depth(), 3);
+    }
+
+    #[test]
+    fn test_dfs_order() {
+        let gh = example();
+        let traversal = gh.dfs(0).traversal();
+
+        assert_eq!(traversal.order, vec![0, 1, 3, 2, 4]);
+        assert_eq!(traversal.parents[&2], 3);
+        assert_eq!(traversal.depth(), 5);
+    }
+
+    #[test]
+    fn test_traversal_from_unknown_node() {
+        let gh = example();
+
+        assert_eq!(gh.bfs(42).count(), 0);
+        assert_eq!(gh.dfs(42).count(), 0);
+    }
+
+    // 0 <-> 1, 0 <-> 2, 1 -> 3, 3 -> 2, 2 -> 4
+    fn example() -> Graph<&'static str> {
+        let mut gh = Graph::new();
+        let nodes = (0..5).map(|_| gh.add_node("node")).collect::<Vec<_>>();
+
+        for &(from, to) in &[(0, 1), (1, 0), (0, 2), (2, 0), (1, 3), (3, 2), (2, 4)] {
+            gh.link(nodes[from], nodes[to], 1);
+        }
+
+        gh
+    }
+}
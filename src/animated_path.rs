@@ -1,24 +1,26 @@
-use crate::pane::{MatrixPane, Surface};
-use graph::algorithm::{self};
+use crate::heuristic::Scaled;
+use crate::pane::{Cell, MatrixPane, Pane, Surface};
+use crate::path_matrix::{self, PathFunc};
+use graph::algorithm::SearchError;
 
-pub fn frames(matrix: &mut MatrixPane, from: usize, look: usize, path_symbol: &str, checked_symbol: &str) -> Vec<String> {
-    let (steps, path) = algorithm::dijkstra_extra(matrix.graph(), from, look);
+pub fn frames(matrix: &mut MatrixPane, from: usize, look: usize, path_symbol: Cell, checked_symbol: Cell, func: PathFunc, heuristic: &Scaled) -> Result<Vec<Pane>, SearchError> {
+    let (steps, result) = path_matrix::search(matrix, from, look, func, heuristic);
+    let result = result?;
     let mut frames = Vec::new();
 
     for step_info in steps {
         for step in step_info {
-            matrix.get_node(step).unwrap().borrow_mut().data = checked_symbol.to_owned();
+            matrix.get_node(step).unwrap().data = checked_symbol;
         }
 
-        frames.push(matrix.pane().to_string());
+        frames.push(matrix.pane());
     }
 
-    for point in &algorithm::path(&path.unwrap(), look, from).unwrap() {
-        matrix.get_node(*point).unwrap().borrow_mut().data = path_symbol.to_owned();
+    for point in result.path.iter().rev() {
+        matrix.get_node(*point).unwrap().data = path_symbol;
 
-        frames.push(matrix.pane().to_string());
+        frames.push(matrix.pane());
     }
 
-    
-    frames
-}
\ No newline at end of file
+    Ok(frames)
+}
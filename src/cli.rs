@@ -0,0 +1,179 @@
+use crate::format::Format;
+use crate::pane::Render;
+
+pub const USAGE: &str = "usage:
+    g2h [--input FILE] [--command COMMAND]... [--no-prompt] [--output FILE]
+    g2h render [--format NAME] [--output FILE] FILE
+
+options:
+    -i, --input FILE       runs commands of the file instead of the ones of stdin
+    -c, --command COMMAND  runs the command before the input, may be repeated, without
+                           --input g2h exits after the last command
+    --no-prompt            doesn't write the prompt, it isn't written when stdin isn't a terminal either
+    -o, --output FILE      writes into the file instead of stdout
+    --color MODE           shows colors as `ansi`, `plain` or `html`, by default colors are
+                           written only into a terminal
+    -f, --format NAME      format of the rendered file, by default it's told by the extension,
+                           a .json file is the one written by the save command
+    -h, --help             shows this message
+
+`render` draws a diagram of the file once and exits, `-` stands for stdin.";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Mode {
+    Session {
+        input: Option<String>,
+        commands: Vec<String>,
+        prompt: bool,
+    },
+    // a format is None for a saved session
+    Render {
+        file: String,
+        format: Option<Format>,
+    },
+    Help,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Options {
+    pub mode: Mode,
+    pub output: Option<String>,
+    pub render: Option<Render>,
+}
+
+pub fn parse_args<I: IntoIterator<Item = String>>(args: I) -> Result<Options, String> {
+    let mut args = args.into_iter().peekable();
+    let render_once = args.peek().is_some_and(|arg| arg == "render");
+    if render_once {
+        args.next();
+    }
+
+    let mut input = None;
+    let mut commands = Vec::new();
+    let mut prompt = true;
+    let mut output = None;
+    let mut format = None;
+    let mut render = None;
+    let mut files = Vec::new();
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or_else(|| format!("{} expects a value", arg));
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(Options { mode: Mode::Help, output: None, render: None }),
+            "-i" | "--input" => input = Some(value()?),
+            "-c" | "--command" => commands.push(value()?),
+            "--no-prompt" => prompt = false,
+            "-o" | "--output" => output = Some(value()?),
+            "-f" | "--format" => {
+                let name = value()?;
+                format = Some(Format::by_name(&name).ok_or_else(|| format!("unknown format {}", name))?);
+            },
+            "--color" => {
+                let name = value()?;
+                render = Some(Render::by_name(&name).ok_or_else(|| format!("unknown color mode {}", name))?);
+            },
+            "-" => files.push(arg),
+            _ if arg.starts_with('-') => return Err(format!("unknown option {}", arg)),
+            _ => files.push(arg),
+        }
+    }
+
+    let mode = if render_once {
+        if input.is_some() || !commands.is_empty() || !prompt {
+            return Err(String::from("render takes only --format, --output and --color"));
+        }
+        let file = match files.as_slice() {
+            [file] => file.clone(),
+            [] => return Err(String::from("render expects a file")),
+            _ => return Err(String::from("render expects a single file")),
+        };
+        let format = match format {
+            Some(format) => Some(format),
+            None if file.ends_with(".json") => None,
+            None => Some(by_extension(&file).ok_or_else(|| format!("can't tell the format of {}, use --format", file))?),
+        };
+
+        Mode::Render { file, format }
+    } else if let Some(file) = files.first() {
+        return Err(format!("unexpected argument {}", file));
+    } else if let Some(format) = format {
+        // a diagram piped into `g2h --format NAME` is rendered as well
+        if input.is_some() || !commands.is_empty() {
+            return Err(String::from("--format is used only to render a diagram"));
+        }
+        Mode::Render { file: String::from("-"), format: Some(format) }
+    } else {
+        Mode::Session { input, commands, prompt }
+    };
+
+    Ok(Options { mode, output, render })
+}
+
+fn by_extension(file: &str) -> Option<Format> {
+    let extension = std::path::Path::new(file).extension()?.to_str()?;
+    match extension {
+        "dot" | "gv" => Some(Format::Dot),
+        "mmd" | "md" => Some(Format::Mermaid),
+        "puml" | "plantuml" => Some(Format::PlantUml),
+        "edges" | "edgelist" => Some(Format::EdgeList),
+        "adj" | "adjlist" => Some(Format::AdjacencyList),
+        "csv" => Some(Format::AdjacencyMatrix),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Options, String> {
+        parse_args(args.iter().map(|arg| arg.to_string()))
+    }
+
+    fn session(input: Option<&str>, commands: &[&str], prompt: bool) -> Mode {
+        Mode::Session {
+            input: input.map(String::from),
+            commands: commands.iter().map(|c| c.to_string()).collect(),
+            prompt,
+        }
+    }
+
+    #[test]
+    fn test_parse_session() {
+        assert_eq!(parse(&[]).unwrap(), Options { mode: session(None, &[], true), output: None, render: None });
+        assert_eq!(
+            parse(&["-i", "steps.txt", "--command", "edge add a", "-c", "print", "--no-prompt", "-o", "out.txt", "--color", "html"]).unwrap(),
+            Options {
+                mode: session(Some("steps.txt"), &["edge add a", "print"], false),
+                output: Some(String::from("out.txt")),
+                render: Some(Render::Html),
+            },
+        );
+    }
+
+    #[test]
+    fn test_parse_render() {
+        assert_eq!(parse(&["render", "deps.dot"]).unwrap().mode, Mode::Render { file: String::from("deps.dot"), format: Some(Format::Dot) });
+        assert_eq!(parse(&["render", "session.json"]).unwrap().mode, Mode::Render { file: String::from("session.json"), format: None });
+        assert_eq!(
+            parse(&["render", "-f", "adjlist", "-", "-o", "deps.txt"]).unwrap(),
+            Options {
+                mode: Mode::Render { file: String::from("-"), format: Some(Format::AdjacencyList) },
+                output: Some(String::from("deps.txt")),
+                render: None,
+            },
+        );
+        assert_eq!(parse(&["--format", "edgelist"]).unwrap().mode, Mode::Render { file: String::from("-"), format: Some(Format::EdgeList) });
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(parse(&["--input"]).unwrap_err(), "--input expects a value");
+        assert_eq!(parse(&["--verbose"]).unwrap_err(), "unknown option --verbose");
+        assert_eq!(parse(&["deps.dot"]).unwrap_err(), "unexpected argument deps.dot");
+        assert_eq!(parse(&["render"]).unwrap_err(), "render expects a file");
+        assert_eq!(parse(&["render", "deps.txt"]).unwrap_err(), "can't tell the format of deps.txt, use --format");
+        assert_eq!(parse(&["render", "-c", "print", "deps.dot"]).unwrap_err(), "render takes only --format, --output and --color");
+        assert_eq!(parse(&["-f", "svg"]).unwrap_err(), "unknown format svg");
+        assert_eq!(parse(&["--color", "sepia"]).unwrap_err(), "unknown color mode sepia");
+    }
+}
@@ -0,0 +1,215 @@
+use std::collections::BTreeMap;
+use std::fmt::Display;
+
+use graph::{Graph, NodeId, Weight};
+
+use super::{fields, quote_field, quoted_fields, DiagramBuilder, ParseError};
+use crate::{line_gh::LineGH, pane::ConnectorType};
+
+fn list_separator(c: char) -> bool {
+    c.is_whitespace() || c == ',' || c == ':'
+}
+
+fn cell_separator(c: char) -> bool {
+    c == ','
+}
+
+// A node and its neighbors per line, `a: b c=3`. A neighbor may carry the weight of the link
+// after `=`, the colon is optional and `#` starts a comment.
+pub fn parse_list(s: &str) -> Result<LineGH, ParseError> {
+    let mut diagram = DiagramBuilder::new();
+    for (i, line) in s.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let fields = quoted_fields(line, list_separator, false).map_err(|message| ParseError::new(i + 1, message))?;
+        let (node, neighbors) = match fields.split_first() {
+            Some(fields) => fields,
+            None => continue,
+        };
+        if !node.tail.is_empty() {
+            return Err(ParseError::new(i + 1, "a quote is followed by a text"));
+        }
+
+        let from = diagram.node(&node.text);
+        for neighbor in neighbors {
+            // a weight is looked for only out of quotes, so names may contain `=`
+            let (name, weight) = match (neighbor.quoted, neighbor.text.rsplit_once('=')) {
+                (true, _) => match neighbor.tail.strip_prefix('=') {
+                    Some(weight) => (neighbor.text.as_str(), Some(weight)),
+                    None if neighbor.tail.is_empty() => (neighbor.text.as_str(), None),
+                    None => return Err(ParseError::new(i + 1, "a quote is followed by a text")),
+                },
+                (false, Some((name, weight))) => (name, Some(weight)),
+                (false, None) => (neighbor.text.as_str(), None),
+            };
+            let weight = match weight {
+                Some(weight) => super::weight(i + 1, weight)?,
+                None => 1,
+            };
+            let to = diagram.node(name);
+            diagram.connect(from, to, weight, None);
+        }
+    }
+
+    Ok(diagram.build(ConnectorType::Arrow))
+}
+
+pub fn to_list<T: Eq + Ord + Display>(gh: &Graph<T>) -> String {
+    let mut list = String::new();
+    for node in gh.nodes() {
+        list.push_str(&quote_field(&node.data.to_string(), list_separator));
+        list.push(':');
+        for link in node.edges.iter().flatten().filter_map(|&e| gh.link_by_index(e)) {
+            let to = gh.node_by_index(link.to).map(|to| quote_field(&to.data.to_string(), list_separator)).unwrap_or_default();
+            match link.weight {
+                1 => list.push_str(&format!(" {}", to)),
+                weight => list.push_str(&format!(" {}={}", to, weight)),
+            }
+        }
+        list.push('\n');
+    }
+
+    list
+}
+
+// A CSV adjacency matrix, a cell holds the weight of the link from the row's node to the
+// column's one, an empty cell or 0 means there is no link. Names come from the header row
+// `,a,b,c` whose first cell is left empty, without it nodes are named by their numbers.
+pub fn parse_matrix(s: &str) -> Result<LineGH, ParseError> {
+    let mut rows = Vec::new();
+    for (i, line) in s.lines().enumerate().filter(|(_, line)| !line.trim().is_empty()) {
+        let cells = fields(line, cell_separator, true).map_err(|message| ParseError::new(i + 1, message))?;
+        rows.push((i + 1, cells.iter().map(|cell| cell.trim().to_owned()).collect::<Vec<_>>()));
+    }
+
+    let header = rows.first().is_some_and(|(_, cells)| {
+        cells[0].is_empty() || cells.iter().any(|cell| !cell.is_empty() && cell.parse::<Weight>().is_err())
+    });
+    let names = match header {
+        true => rows.remove(0).1.split_off(1),
+        false => (0..rows.first().map_or(0, |(_, cells)| cells.len())).map(|i| i.to_string()).collect(),
+    };
+
+    if rows.len() != names.len() {
+        return Err(ParseError::new(1, &format!("the matrix has {} rows instead of {}", rows.len(), names.len())));
+    }
+
+    let mut diagram = DiagramBuilder::new();
+    let nodes = names.iter().map(|name| diagram.node(name)).collect::<Vec<_>>();
+    for (from, (line, mut cells)) in rows.into_iter().enumerate() {
+        if header {
+            let name = cells.remove(0);
+            if !name.is_empty() && name != names[from] {
+                return Err(ParseError::new(line, &format!("the row {} doesn't match the column {}", name, names[from])));
+            }
+        }
+        if cells.len() != names.len() {
+            return Err(ParseError::new(line, &format!("the row has {} cells instead of {}", cells.len(), names.len())));
+        }
+
+        for (to, cell) in cells.iter().enumerate().filter(|(_, cell)| !cell.is_empty()) {
+            match super::weight(line, cell)? {
+                0 => (),
+                weight => diagram.connect(nodes[from], nodes[to], weight, None),
+            }
+        }
+    }
+
+    Ok(diagram.build(ConnectorType::Arrow))
+}
+
+// zero weighted links can't be told apart from missing ones, when there are several links
+// between two nodes the first one is written
+pub fn to_matrix<T: Eq + Ord + Display>(gh: &Graph<T>) -> String {
+    let columns = gh.nodes().enumerate().map(|(column, node)| (node.index_in, column)).collect::<BTreeMap<NodeId, usize>>();
+
+    let mut matrix = String::new();
+    for node in gh.nodes() {
+        matrix.push(',');
+        matrix.push_str(&quote_field(&node.data.to_string(), cell_separator));
+    }
+    matrix.push('\n');
+
+    for node in gh.nodes() {
+        let mut cells = vec![None; columns.len()];
+        for link in node.edges.iter().flatten().filter_map(|&e| gh.link_by_index(e)) {
+            if let Some(&column) = columns.get(&link.to) {
+                cells[column].get_or_insert(link.weight);
+            }
+        }
+
+        matrix.push_str(&quote_field(&node.data.to_string(), cell_separator));
+        for cell in cells {
+            matrix.push(',');
+            matrix.push_str(&cell.unwrap_or(0).to_string());
+        }
+        matrix.push('\n');
+    }
+
+    matrix
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_list() {
+        let gh = parse_list("# roads\na: b c=3\nb c\n\"New: York\": a=12\n").unwrap();
+
+        assert_eq!(gh.boxes(), &["a", "b", "c", "New: York"]);
+        assert_eq!(gh.graph().neighbors(0), vec![(1, 1), (2, 3)]);
+        assert_eq!(gh.graph().neighbors(1), vec![(2, 1)]);
+        assert_eq!(gh.graph().neighbors(3), vec![(0, 12)]);
+        assert_eq!(parse_list("a: b=x").unwrap_err(), ParseError::new(1, "x isn't a weight"));
+        assert_eq!(parse_list("\"a\": \"b\"c").unwrap_err(), ParseError::new(1, "a quote is followed by a text"));
+
+        // a line of separators only declares nothing
+        let gh = parse_list(":\na: \"b=c\"=2 \"d=\"\n, :\n").unwrap();
+        assert_eq!(gh.boxes(), &["a", "b=c", "d="]);
+        assert_eq!(gh.graph().neighbors(0), vec![(1, 2), (2, 1)]);
+    }
+
+    #[test]
+    fn test_list_round_trip() {
+        let gh = parse_list("a: b c=3 \"a=b\"=4\nb c\n\"New: York\": a=12\n").unwrap().graph();
+        let list = to_list(&gh);
+
+        assert_eq!(list, "a: b c=3 \"a=b\"=4\nb: c\nc:\n\"a=b\":\n\"New: York\": a=12\n");
+        assert_eq!(parse_list(&list).unwrap().graph().to_json().unwrap(), gh.to_json().unwrap());
+    }
+
+    #[test]
+    fn test_parse_matrix() {
+        let gh = parse_matrix(",a,b,\"c, d\"\na,0,5,\nb,,0,2\n\"c, d\",7,0,0\n").unwrap();
+
+        assert_eq!(gh.boxes(), &["a", "b", "c, d"]);
+        assert_eq!(gh.graph().neighbors(0), vec![(1, 5)]);
+        assert_eq!(gh.graph().neighbors(1), vec![(2, 2)]);
+        assert_eq!(gh.graph().neighbors(2), vec![(0, 7)]);
+
+        let gh = parse_matrix("0,1\n1,0\n").unwrap();
+        assert_eq!(gh.boxes(), &["0", "1"]);
+        assert_eq!(gh.graph().neighbors(1), vec![(0, 1)]);
+    }
+
+    #[test]
+    fn test_matrix_round_trip() {
+        let gh = parse_matrix(",a,b,\"c, d\"\na,0,5,0\nb,0,0,2\n\"c, d\",7,0,0\n").unwrap().graph();
+        let matrix = to_matrix(&gh);
+
+        assert_eq!(matrix, ",a,b,\"c, d\"\na,0,5,0\nb,0,0,2\n\"c, d\",7,0,0\n");
+        assert_eq!(parse_matrix(&matrix).unwrap().graph().to_json().unwrap(), gh.to_json().unwrap());
+    }
+
+    #[test]
+    fn test_parse_matrix_errors() {
+        assert_eq!(parse_matrix(",a,b\na,0,1\n").unwrap_err(), ParseError::new(1, "the matrix has 1 rows instead of 2"));
+        assert_eq!(parse_matrix(",a,b\na,0,1\nb,0\n").unwrap_err(), ParseError::new(3, "the row has 1 cells instead of 2"));
+        assert_eq!(parse_matrix(",a,b\nb,0,1\na,0,0\n").unwrap_err(), ParseError::new(2, "the row b doesn't match the column a"));
+        assert_eq!(parse_matrix("0,1\n1,x\n").unwrap_err(), ParseError::new(2, "x isn't a weight"));
+    }
+}
@@ -0,0 +1,530 @@
+use std::collections::BTreeMap;
+use std::fmt::Display;
+
+use graph::{Graph, Weight};
+
+use super::{DiagramBuilder, ParseError};
+use crate::{line_gh::LineGH, pane::ConnectorType};
+
+pub fn graph_to_dot<T: Eq + Ord + Display>(gh: &Graph<T>) -> String {
+    let mut dot = String::from("digraph {\n");
+    for node in gh.nodes() {
+        dot.push_str(&format!("    {} [label={}];\n", node.index_in, quote(&node.data.to_string())));
+    }
+    for node in gh.nodes() {
+        for link in node.edges.iter().flatten().filter_map(|&e| gh.link_by_index(e)) {
+            dot.push_str(&format!("    {} -> {} [weight={}];\n", link.from, link.to, link.weight));
+        }
+    }
+    dot.push_str("}\n");
+
+    dot
+}
+
+// arrows are kept only when the diagram draws them, otherwise it's exported as an undirected graph
+pub fn to_dot(gh: &LineGH) -> String {
+    let (kind, op) = match gh.pane_settings.connection_type {
+        ConnectorType::Arrow => ("digraph", "->"),
+        ConnectorType::General => ("graph", "--"),
+    };
+
+    let mut dot = format!("{} {{\n", kind);
+    for (i, label) in gh.boxes().iter().enumerate() {
+        dot.push_str(&format!("    {} [label={}];\n", i, quote(label)));
+    }
+    for (from, connection) in gh.connections() {
+        let mut attributes = Vec::new();
+        if connection.weight != 1 {
+            attributes.push(format!("weight={}", connection.weight));
+        }
+        if let Some(label) = &connection.label {
+            attributes.push(format!("label={}", quote(label)));
+        }
+
+        match attributes.is_empty() {
+            true => dot.push_str(&format!("    {} {} {};\n", from, op, connection.to)),
+            false => dot.push_str(&format!("    {} {} {} [{}];\n", from, op, connection.to, attributes.join(", "))),
+        }
+    }
+    dot.push_str("}\n");
+
+    dot
+}
+
+fn quote(s: &str) -> String {
+    let escaped = s.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n");
+    format!("\"{}\"", escaped)
+}
+
+// Builds a diagram from a DOT graph. Nodes become boxes in the order they first appear,
+// subgraphs are flattened and only `label` and `weight` attributes are taken into account.
+pub fn parse(s: &str) -> Result<LineGH, ParseError> {
+    let tokens = tokenize(s)?;
+    let mut parser = Parser {
+        tokens,
+        position: 0,
+        directed: false,
+        diagram: DiagramBuilder::new(),
+        scopes: Vec::new(),
+    };
+    parser.graph()?;
+
+    let connection_type = match parser.directed {
+        true => ConnectorType::Arrow,
+        false => ConnectorType::General,
+    };
+
+    Ok(parser.diagram.build(connection_type))
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Id(String),
+    Edge,
+    Open,
+    Close,
+    OpenAttributes,
+    CloseAttributes,
+    Equal,
+    Separator,
+    Colon,
+}
+
+fn tokenize(s: &str) -> Result<Vec<(usize, Token)>, ParseError> {
+    let chars = s.chars().collect::<Vec<char>>();
+    let mut tokens = Vec::new();
+    let mut line = 1;
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let next = chars.get(i + 1).cloned();
+        match c {
+            '\n' => {
+                line += 1;
+                i += 1;
+            },
+            c if c.is_whitespace() => i += 1,
+            '/' if next == Some('/') => {
+                while i < chars.len() && chars[i] != '\n' {
+                    i += 1;
+                }
+            },
+            '#' if tokens.last().is_none_or(|&(l, _)| l < line) => {
+                while i < chars.len() && chars[i] != '\n' {
+                    i += 1;
+                }
+            },
+            '/' if next == Some('*') => {
+                i += 2;
+                while i < chars.len() && !(chars[i] == '*' && chars.get(i + 1) == Some(&'/')) {
+                    if chars[i] == '\n' {
+                        line += 1;
+                    }
+                    i += 1;
+                }
+                if i >= chars.len() {
+                    return Err(ParseError::new(line, "unterminated comment"));
+                }
+                i += 2;
+            },
+            '-' if next == Some('>') || next == Some('-') => {
+                tokens.push((line, Token::Edge));
+                i += 2;
+            },
+            '{' | '}' | '[' | ']' | '=' | ';' | ',' | ':' => {
+                let token = match c {
+                    '{' => Token::Open,
+                    '}' => Token::Close,
+                    '[' => Token::OpenAttributes,
+                    ']' => Token::CloseAttributes,
+                    '=' => Token::Equal,
+                    ':' => Token::Colon,
+                    _ => Token::Separator,
+                };
+                tokens.push((line, token));
+                i += 1;
+            },
+            '"' => {
+                let start = line;
+                let mut id = String::new();
+                i += 1;
+                loop {
+                    match (chars.get(i), chars.get(i + 1)) {
+                        (None, _) => return Err(ParseError::new(start, "unterminated string")),
+                        (Some('"'), _) => break,
+                        (Some('\\'), Some('n')) | (Some('\\'), Some('l')) | (Some('\\'), Some('r')) => {
+                            id.push('\n');
+                            i += 2;
+                        },
+                        (Some('\\'), Some('"')) | (Some('\\'), Some('\\')) => {
+                            id.push(chars[i + 1]);
+                            i += 2;
+                        },
+                        (Some('\\'), Some('\n')) => {
+                            line += 1;
+                            i += 2;
+                        },
+                        (Some(&c), _) => {
+                            if c == '\n' {
+                                line += 1;
+                            }
+                            id.push(c);
+                            i += 1;
+                        },
+                    }
+                }
+                tokens.push((start, Token::Id(id)));
+                i += 1;
+            },
+            '<' => {
+                let start = line;
+                let mut depth = 1;
+                let mut id = String::new();
+                i += 1;
+                loop {
+                    match chars.get(i) {
+                        None => return Err(ParseError::new(start, "unterminated html string")),
+                        Some('<') => depth += 1,
+                        Some('>') => depth -= 1,
+                        Some('\n') => line += 1,
+                        _ => (),
+                    }
+                    if depth == 0 {
+                        break;
+                    }
+                    id.push(chars[i]);
+                    i += 1;
+                }
+                tokens.push((start, Token::Id(id)));
+                i += 1;
+            },
+            c if c.is_alphanumeric() || c == '_' || c == '.' || c == '-' => {
+                let mut id = String::new();
+                while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_' || chars[i] == '.' || (chars[i] == '-' && id.is_empty())) {
+                    id.push(chars[i]);
+                    i += 1;
+                }
+                tokens.push((line, Token::Id(id)));
+            },
+            c => return Err(ParseError::new(line, &format!("unexpected character {:?}", c))),
+        }
+    }
+
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<(usize, Token)>,
+    position: usize,
+    directed: bool,
+    diagram: DiagramBuilder,
+    // nodes mentioned in each of subgraphs which are being parsed
+    scopes: Vec<Vec<usize>>,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position).map(|(_, token)| token)
+    }
+
+    fn peek_keyword(&self, keyword: &str) -> bool {
+        match self.peek() {
+            Some(Token::Id(id)) => id.eq_ignore_ascii_case(keyword),
+            _ => false,
+        }
+    }
+
+    fn line(&self) -> usize {
+        match self.tokens.get(self.position).or_else(|| self.tokens.last()) {
+            Some((line, _)) => *line,
+            None => 1,
+        }
+    }
+
+    fn error<T>(&self, message: &str) -> Result<T, ParseError> {
+        Err(ParseError::new(self.line(), message))
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.peek().cloned();
+        self.position += 1;
+        token
+    }
+
+    fn expect(&mut self, expected: Token, message: &str) -> Result<(), ParseError> {
+        match self.peek() {
+            Some(token) if *token == expected => {
+                self.position += 1;
+                Ok(())
+            },
+            _ => self.error(message),
+        }
+    }
+
+    fn id(&mut self) -> Result<String, ParseError> {
+        match self.peek().cloned() {
+            Some(Token::Id(id)) => {
+                self.position += 1;
+                Ok(id)
+            },
+            _ => self.error("expected an identifier"),
+        }
+    }
+
+    fn node(&mut self, name: String) -> usize {
+        let i = self.diagram.node(&name);
+
+        for scope in &mut self.scopes {
+            if !scope.contains(&i) {
+                scope.push(i);
+            }
+        }
+
+        i
+    }
+
+    fn graph(&mut self) -> Result<(), ParseError> {
+        if self.peek_keyword("strict") {
+            self.position += 1;
+        }
+        if self.peek_keyword("digraph") {
+            self.directed = true;
+        } else if !self.peek_keyword("graph") {
+            return self.error("expected graph or digraph");
+        }
+        self.position += 1;
+
+        if let Some(Token::Id(_)) = self.peek() {
+            self.position += 1;
+        }
+        self.expect(Token::Open, "expected {")?;
+        self.statements()?;
+        self.expect(Token::Close, "expected }")?;
+
+        match self.peek() {
+            Some(_) => self.error("unexpected statement after the graph"),
+            None => Ok(()),
+        }
+    }
+
+    fn statements(&mut self) -> Result<(), ParseError> {
+        loop {
+            match self.peek() {
+                None | Some(Token::Close) => return Ok(()),
+                Some(Token::Separator) => self.position += 1,
+                _ => self.statement()?,
+            }
+        }
+    }
+
+    fn statement(&mut self) -> Result<(), ParseError> {
+        let is_default = ["graph", "node", "edge"].iter().any(|keyword| self.peek_keyword(keyword));
+        if is_default && self.tokens.get(self.position + 1).map(|(_, token)| token) == Some(&Token::OpenAttributes) {
+            self.position += 1;
+            self.attributes()?;
+            return Ok(());
+        }
+        if self.tokens.get(self.position + 1).map(|(_, token)| token) == Some(&Token::Equal) {
+            self.id()?;
+            self.position += 1;
+            self.id()?;
+            return Ok(());
+        }
+
+        let start = self.position;
+        let (mut from, is_node) = self.operand()?;
+        let mut links = Vec::new();
+        while self.peek() == Some(&Token::Edge) {
+            self.position += 1;
+            let (to, _) = self.operand()?;
+            for &a in &from {
+                for &b in &to {
+                    links.push((a, b));
+                }
+            }
+            from = to;
+        }
+
+        let line = self.line();
+        let attributes = match self.peek() {
+            Some(Token::OpenAttributes) => self.attributes()?,
+            _ => BTreeMap::new(),
+        };
+
+        if links.is_empty() {
+            if let (true, Some(label)) = (is_node, attributes.get("label")) {
+                let name = self.tokens[start].1.clone();
+                if let Token::Id(name) = name {
+                    self.diagram.label(from[0], &label.replace("\\N", &name));
+                }
+            }
+            return Ok(());
+        }
+
+        let weight = match attributes.get("weight") {
+            Some(weight) => match weight.parse::<f64>().map(f64::round) {
+                // the upper bound is 2^63 exactly, it doesn't fit itself
+                Ok(rounded) if rounded >= Weight::MIN as f64 && rounded < Weight::MAX as f64 => rounded as Weight,
+                Ok(_) => return Err(ParseError::new(line, &format!("weight {} is out of range", weight))),
+                Err(_) => return Err(ParseError::new(line, &format!("weight {} is not a number", weight))),
+            },
+            None => 1,
+        };
+        let label = attributes.get("label").cloned();
+        for (a, b) in links {
+            self.diagram.connect(a, b, weight, label.clone());
+        }
+
+        Ok(())
+    }
+
+    // a node or a subgraph, the flag tells whether it was a single node
+    fn operand(&mut self) -> Result<(Vec<usize>, bool), ParseError> {
+        if self.peek_keyword("subgraph") || self.peek() == Some(&Token::Open) {
+            return Ok((self.subgraph()?, false));
+        }
+
+        let name = self.id()?;
+        let node = self.node(name);
+        while self.peek() == Some(&Token::Colon) {
+            self.position += 1;
+            self.id()?;
+        }
+
+        Ok((vec![node], true))
+    }
+
+    fn subgraph(&mut self) -> Result<Vec<usize>, ParseError> {
+        if self.peek_keyword("subgraph") {
+            self.position += 1;
+            if let Some(Token::Id(_)) = self.peek() {
+                self.position += 1;
+            }
+        }
+
+        self.expect(Token::Open, "expected {")?;
+        self.scopes.push(Vec::new());
+        let parsed = self.statements();
+        let nodes = self.scopes.pop().unwrap_or_default();
+        parsed?;
+        self.expect(Token::Close, "expected }")?;
+
+        Ok(nodes)
+    }
+
+    fn attributes(&mut self) -> Result<BTreeMap<String, String>, ParseError> {
+        let mut attributes = BTreeMap::new();
+        while self.peek() == Some(&Token::OpenAttributes) {
+            self.position += 1;
+            loop {
+                match self.next() {
+                    Some(Token::CloseAttributes) => break,
+                    Some(Token::Separator) => (),
+                    Some(Token::Id(key)) => {
+                        let value = match self.peek() {
+                            Some(Token::Equal) => {
+                                self.position += 1;
+                                self.id()?
+                            },
+                            _ => String::from("true"),
+                        };
+                        attributes.insert(key, value);
+                    },
+                    _ => {
+                        self.position -= 1;
+                        return self.error("expected an attribute");
+                    },
+                }
+            }
+        }
+
+        Ok(attributes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_digraph() {
+        let dot = r#"
+            // dependencies of the project
+            digraph deps {
+                rankdir=LR;
+                node [shape=box];
+                app [label="g2h"];
+                app -> graph [label="uses", weight=3];
+                app -> regex -> "aho corasick";
+                /* a loop back */
+                graph -> app
+            }
+        "#;
+        let gh = parse(dot).unwrap();
+
+        assert_eq!(gh.boxes(), &["g2h", "graph", "regex", "aho corasick"]);
+        assert_eq!(gh.pane_settings.connection_type, ConnectorType::Arrow);
+
+        let connections = gh.connections()
+            .map(|(from, c)| (from, c.to, c.weight, c.label.clone()))
+            .collect::<Vec<_>>();
+        assert_eq!(connections, vec![
+            (0, 1, 3, Some(String::from("uses"))),
+            (0, 2, 1, None),
+            (1, 0, 1, None),
+            (2, 3, 1, None),
+        ]);
+    }
+
+    #[test]
+    fn test_parse_subgraphs_and_undirected_edges() {
+        let dot = "strict graph { a -- { b c }; subgraph cluster { d -- e } c -- d [weight=2.4] }";
+        let gh = parse(dot).unwrap();
+
+        assert_eq!(gh.boxes(), &["a", "b", "c", "d", "e"]);
+        assert_eq!(gh.pane_settings.connection_type, ConnectorType::General);
+
+        let connections = gh.connections().map(|(from, c)| (from, c.to, c.weight)).collect::<Vec<_>>();
+        assert_eq!(connections, vec![(0, 1, 1), (0, 2, 1), (2, 3, 2), (3, 4, 1)]);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(parse("digraph { a -> }").unwrap_err(), ParseError::new(1, "expected an identifier"));
+        assert_eq!(parse("tree { }").unwrap_err(), ParseError::new(1, "expected graph or digraph"));
+        assert_eq!(parse("digraph {\n a -> b [weight=heavy]\n}").unwrap_err().line, 2);
+        assert_eq!(parse("digraph { a -> b [weight=1e30] }").unwrap_err(), ParseError::new(1, "weight 1e30 is out of range"));
+        assert_eq!(parse("digraph { a -> b [weight=inf] }").unwrap_err(), ParseError::new(1, "weight inf is out of range"));
+        assert_eq!(parse("digraph { a -> b [weight=NaN] }").unwrap_err(), ParseError::new(1, "weight NaN is out of range"));
+        assert_eq!(parse("digraph { \"a }").unwrap_err(), ParseError::new(1, "unterminated string"));
+    }
+
+    #[test]
+    fn test_export_and_import() {
+        let mut gh = LineGH::new();
+        gh.pane_settings.connection_type = ConnectorType::Arrow;
+        gh.add_edge("hello \"world\"");
+        gh.add_edge("two\nlines");
+        gh.connect(0, 1);
+        gh.connect_with(1, 0, 5, Some(String::from("back")));
+
+        let dot = to_dot(&gh);
+        assert_eq!(dot, "digraph {\n    0 [label=\"hello \\\"world\\\"\"];\n    1 [label=\"two\\nlines\"];\n    0 -> 1;\n    1 -> 0 [weight=5, label=\"back\"];\n}\n");
+
+        let imported = parse(&dot).unwrap();
+        assert_eq!(imported.boxes(), gh.boxes());
+        assert_eq!(imported.connections().collect::<Vec<_>>(), gh.connections().collect::<Vec<_>>());
+        assert_eq!(imported.to_string(), gh.to_string());
+    }
+
+    #[test]
+    fn test_graph_to_dot() {
+        let mut gh = Graph::new();
+        let a = gh.add_node("a");
+        let b = gh.add_node("b");
+        gh.link(a, b, 10);
+        gh.link(b, a, -2);
+
+        assert_eq!(graph_to_dot(&gh), "digraph {\n    0 [label=\"a\"];\n    1 [label=\"b\"];\n    0 -> 1 [weight=10];\n    1 -> 0 [weight=-2];\n}\n");
+    }
+}
@@ -0,0 +1,92 @@
+use std::fmt::Display;
+
+use graph::Graph;
+
+use super::{fields, quote_field, DiagramBuilder, ParseError};
+use crate::{line_gh::LineGH, pane::ConnectorType};
+
+fn separator(c: char) -> bool {
+    c.is_whitespace() || c == ','
+}
+
+// One `from to weight` link per line. The weight may be omitted, a line with a single name
+// declares a node without links. Names with spaces or commas are quoted, `#` starts a comment.
+pub fn parse(s: &str) -> Result<LineGH, ParseError> {
+    let mut diagram = DiagramBuilder::new();
+    for (i, line) in s.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let fields = fields(line, separator, false).map_err(|message| ParseError::new(i + 1, message))?;
+        match fields.as_slice() {
+            [name] => {
+                diagram.node(name);
+            },
+            [from, to] => {
+                let (from, to) = (diagram.node(from), diagram.node(to));
+                diagram.connect(from, to, 1, None);
+            },
+            [from, to, weight] => {
+                let weight = super::weight(i + 1, weight)?;
+                let (from, to) = (diagram.node(from), diagram.node(to));
+                diagram.connect(from, to, weight, None);
+            },
+            _ => return Err(ParseError::new(i + 1, "expected from, to and weight")),
+        }
+    }
+
+    Ok(diagram.build(ConnectorType::Arrow))
+}
+
+pub fn to_edge_list<T: Eq + Ord + Display>(gh: &Graph<T>) -> String {
+    let mut list = String::new();
+    for node in gh.nodes() {
+        let name = quote_field(&node.data.to_string(), separator);
+        let links = node.edges.iter().flatten().filter_map(|&e| gh.link_by_index(e)).collect::<Vec<_>>();
+        if links.is_empty() {
+            list.push_str(&format!("{}\n", name));
+        }
+        for link in links {
+            let to = gh.node_by_index(link.to).map(|to| quote_field(&to.data.to_string(), separator)).unwrap_or_default();
+            match link.weight {
+                1 => list.push_str(&format!("{} {}\n", name, to)),
+                weight => list.push_str(&format!("{} {} {}\n", name, to, weight)),
+            }
+        }
+    }
+
+    list
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_edge_list() {
+        let gh = parse("# roads\na b 5\nb, c\n\"New York\" a 12\nlonely\n").unwrap();
+
+        assert_eq!(gh.boxes(), &["a", "b", "c", "New York", "lonely"]);
+        assert_eq!(gh.graph().neighbors(0), vec![(1, 5)]);
+        assert_eq!(gh.graph().neighbors(1), vec![(2, 1)]);
+        assert_eq!(gh.graph().neighbors(3), vec![(0, 12)]);
+    }
+
+    #[test]
+    fn test_edge_list_round_trip() {
+        let gh = parse("a b 5\nb c\n\"New York\" a 12\nlonely\n").unwrap().graph();
+        let list = to_edge_list(&gh);
+
+        assert_eq!(list, "a b 5\nb c\nc\n\"New York\" a 12\nlonely\n");
+        assert_eq!(parse(&list).unwrap().graph().to_json().unwrap(), gh.to_json().unwrap());
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(parse("a b 5\na b five").unwrap_err(), ParseError::new(2, "five isn't a weight"));
+        assert_eq!(parse("a b 5 6").unwrap_err(), ParseError::new(1, "expected from, to and weight"));
+        assert_eq!(parse("\"a b 5").unwrap_err(), ParseError::new(1, "unterminated quote"));
+    }
+}
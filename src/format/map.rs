@@ -0,0 +1,133 @@
+use graph::Weight;
+
+use super::ParseError;
+use crate::pane::{Cell, MatrixPane};
+
+// the weight of entering a free cell, the same as the one of an empty matrix
+const FREE_WEIGHT: Weight = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Terrain {
+    Wall,
+    // how many times entering the cell costs more than entering a free one
+    Rough(Weight),
+}
+
+impl Terrain {
+    // `.` `G` `S` are passable and `@` `O` `T` `W` are not, as in pathfinding benchmark maps
+    fn from_char(c: char) -> Option<Terrain> {
+        match c {
+            '.' | 'G' | 'S' => Some(Terrain::Rough(1)),
+            '@' | 'O' | 'T' | 'W' => Some(Terrain::Wall),
+            c => c.to_digit(10).map(|digit| Terrain::Rough(Weight::from(digit))),
+        }
+    }
+}
+
+// Builds a matrix from a grid map. The `type`/`height`/`width`/`map` header of benchmark
+// maps is optional, without it every line of the file is a row of the grid.
+pub fn parse(s: &str, c: Cell) -> Result<MatrixPane, ParseError> {
+    let (width, height, cells) = grid(s)?;
+
+    let mut matrix = MatrixPane::new(width, height, c);
+    for (i, &terrain) in cells.iter().enumerate() {
+        if let Terrain::Rough(times) = terrain {
+            if times != 1 {
+                matrix.set_terrain(i, times * FREE_WEIGHT);
+            }
+        }
+    }
+    for (i, &terrain) in cells.iter().enumerate() {
+        if terrain == Terrain::Wall {
+            matrix.block(i);
+        }
+    }
+
+    Ok(matrix)
+}
+
+// width, height and cells of the grid row by row
+fn grid(s: &str) -> Result<(usize, usize, Vec<Terrain>), ParseError> {
+    let mut lines = s.lines().enumerate().map(|(i, line)| (i + 1, line.trim_end())).peekable();
+    let mut declared = (None, None);
+    if lines.peek().is_some_and(|(_, line)| line.starts_with("type")) {
+        loop {
+            let (number, line) = match lines.next() {
+                Some(line) => line,
+                None => return Err(ParseError::new(1, "the map section is missing")),
+            };
+            let mut words = line.split_whitespace();
+            match (words.next(), words.next()) {
+                (Some("map"), None) => break,
+                (Some("type"), _) => (),
+                (Some("height"), Some(n)) => declared.1 = Some(size(number, n)?),
+                (Some("width"), Some(n)) => declared.0 = Some(size(number, n)?),
+                _ => return Err(ParseError::new(number, "unexpected line in the header")),
+            }
+        }
+    }
+
+    let mut rows = Vec::new();
+    for (number, line) in lines.filter(|(_, line)| !line.is_empty()) {
+        let row = line.chars()
+            .map(|c| Terrain::from_char(c).ok_or_else(|| ParseError::new(number, &format!("unknown cell {:?}", c))))
+            .collect::<Result<Vec<Terrain>, ParseError>>()?;
+        rows.push((number, row));
+    }
+
+    let width = declared.0.unwrap_or_else(|| rows.first().map_or(0, |(_, row)| row.len()));
+    let height = declared.1.unwrap_or(rows.len());
+    if width == 0 || height == 0 {
+        return Err(ParseError::new(1, "the map has no cells"));
+    }
+    if let Some((number, row)) = rows.iter().find(|(_, row)| row.len() != width) {
+        return Err(ParseError::new(*number, &format!("the row has {} cells instead of {}", row.len(), width)));
+    }
+    if rows.len() != height {
+        return Err(ParseError::new(1, &format!("the map has {} rows instead of {}", rows.len(), height)));
+    }
+
+    Ok((width, height, rows.into_iter().flat_map(|(_, row)| row).collect()))
+}
+
+fn size(line: usize, n: &str) -> Result<usize, ParseError> {
+    n.parse().map_err(|_| ParseError::new(line, &format!("{} isn't a size", n)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pane::Surface;
+    use graph::algorithm;
+
+    #[test]
+    fn test_parse_benchmark_map() {
+        let map = "type octile\nheight 3\nwidth 4\nmap\n....\n.@T.\n....\n";
+        let matrix = parse(map, Cell::new('.')).unwrap();
+
+        assert_eq!(matrix.pane().to_string(), ". . . .\n.     .\n. . . .");
+        assert!(matrix.graph().is_blocked(5));
+        assert!(matrix.graph().is_blocked(6));
+        assert_eq!(algorithm::dijkstra(matrix.graph(), 4, 7).unwrap().cost, 50);
+    }
+
+    #[test]
+    fn test_parse_terrain_weights() {
+        let matrix = parse("..9\n.3.\n", Cell::new('.')).unwrap();
+
+        assert_eq!(matrix.graph().neighbors(0), vec![(1, 10), (3, 10)]);
+        assert_eq!(matrix.graph().neighbors(1), vec![(0, 10), (2, 90), (4, 30)]);
+        assert_eq!(algorithm::dijkstra(matrix.graph(), 0, 5).unwrap().cost, 50);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(parse("..\n.x\n", Cell::new('.')).err(), Some(ParseError::new(2, "unknown cell 'x'")));
+        assert_eq!(parse("..\n...\n", Cell::new('.')).err(), Some(ParseError::new(2, "the row has 3 cells instead of 2")));
+        assert_eq!(parse("type octile\nheight 3\nwidth 2\nmap\n..\n", Cell::new('.')).err(), Some(ParseError::new(1, "the map has 1 rows instead of 3")));
+        assert_eq!(parse("type octile\nheight many\n", Cell::new('.')).err(), Some(ParseError::new(2, "many isn't a size")));
+        assert_eq!(parse("type octile\nheight 1\n", Cell::new('.')).err(), Some(ParseError::new(1, "the map section is missing")));
+        assert_eq!(parse("\n\n", Cell::new('.')).err(), Some(ParseError::new(1, "the map has no cells")));
+        assert_eq!(parse("type octile\nheight 0\nwidth 4\nmap\n", Cell::new('.')).err(), Some(ParseError::new(1, "the map has no cells")));
+    }
+}
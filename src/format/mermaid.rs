@@ -0,0 +1,290 @@
+use regex::Regex;
+
+use super::{DiagramBuilder, ParseError};
+use crate::{line_gh::LineGH, pane::ConnectorType};
+
+// Contents of ```mermaid blocks of a markdown document in the order they are written.
+pub fn blocks(markdown: &str) -> Vec<String> {
+    let mut blocks = Vec::new();
+    let mut block: Option<Vec<&str>> = None;
+    for line in markdown.lines() {
+        let fence = line.trim();
+        match &mut block {
+            Some(lines) if fence.starts_with("```") => {
+                blocks.push(lines.join("\n"));
+                block = None;
+            },
+            Some(lines) => lines.push(line),
+            None if fence.starts_with("```") && fence[3..].trim() == "mermaid" => block = Some(Vec::new()),
+            None => (),
+        }
+    }
+
+    blocks
+}
+
+// Builds a diagram from a subset of the flowchart syntax: `graph`/`flowchart` header, nodes with
+// shapes, chained links with `&` groups and link texts. Subgraphs are flattened, styling is ignored.
+// A markdown document is accepted as well, then its first mermaid block is used.
+pub fn parse(s: &str) -> Result<LineGH, ParseError> {
+    let blocks = blocks(s);
+    let s = blocks.first().map(String::as_str).unwrap_or(s);
+
+    let header = Regex::new(r"^(graph|flowchart)(\s+(TB|TD|BT|RL|LR))?$").unwrap();
+    let ignored = Regex::new(r"^(subgraph|end|classDef|class|style|linkStyle|click|direction)\b").unwrap();
+
+    let mut diagram = DiagramBuilder::new();
+    let mut arrows = false;
+    let mut has_header = false;
+    for (i, line) in s.lines().enumerate() {
+        let line_number = i + 1;
+        let line = line.trim();
+        if line.starts_with("%%") {
+            continue;
+        }
+
+        for statement in line.split(';').map(str::trim).filter(|s| !s.is_empty()) {
+            if !has_header {
+                if !header.is_match(statement) {
+                    return Err(ParseError::new(line_number, "expected graph or flowchart"));
+                }
+                has_header = true;
+            } else if !ignored.is_match(statement) {
+                let mut parser = Statement { text: statement, position: 0, line: line_number };
+                arrows |= parser.parse(&mut diagram)?;
+            }
+        }
+    }
+
+    if !has_header {
+        return Err(ParseError::new(1, "expected graph or flowchart"));
+    }
+
+    let connection_type = match arrows {
+        true => ConnectorType::Arrow,
+        false => ConnectorType::General,
+    };
+
+    Ok(diagram.build(connection_type))
+}
+
+struct Link {
+    label: Option<String>,
+    arrow: bool,
+    both: bool,
+    invisible: bool,
+}
+
+struct Statement<'a> {
+    text: &'a str,
+    position: usize,
+    line: usize,
+}
+
+impl<'a> Statement<'a> {
+    fn rest(&self) -> &'a str {
+        &self.text[self.position..]
+    }
+
+    fn skip_spaces(&mut self) {
+        let rest = self.rest();
+        self.position += rest.len() - rest.trim_start().len();
+    }
+
+    fn error<T>(&self, message: &str) -> Result<T, ParseError> {
+        Err(ParseError::new(self.line, message))
+    }
+
+    // tells whether any of links had an arrow head
+    fn parse(&mut self, diagram: &mut DiagramBuilder) -> Result<bool, ParseError> {
+        let mut arrows = false;
+        let mut from = self.group(diagram)?;
+        loop {
+            self.skip_spaces();
+            if self.rest().is_empty() {
+                return Ok(arrows);
+            }
+
+            let link = self.link()?;
+            let to = self.group(diagram)?;
+            if !link.invisible {
+                for &a in &from {
+                    for &b in &to {
+                        diagram.connect(a, b, 1, link.label.clone());
+                        if link.both {
+                            diagram.connect(b, a, 1, link.label.clone());
+                        }
+                    }
+                }
+            }
+
+            arrows |= link.arrow;
+            from = to;
+        }
+    }
+
+    fn group(&mut self, diagram: &mut DiagramBuilder) -> Result<Vec<usize>, ParseError> {
+        let mut nodes = vec![self.node(diagram)?];
+        loop {
+            self.skip_spaces();
+            if !self.rest().starts_with('&') {
+                return Ok(nodes);
+            }
+            self.position += 1;
+            nodes.push(self.node(diagram)?);
+        }
+    }
+
+    fn node(&mut self, diagram: &mut DiagramBuilder) -> Result<usize, ParseError> {
+        self.skip_spaces();
+        let rest = self.rest();
+        let length = rest.find(|c: char| !(c.is_alphanumeric() || c == '_')).unwrap_or(rest.len());
+        if length == 0 {
+            return self.error("expected a node");
+        }
+
+        let node = diagram.node(&rest[..length]);
+        self.position += length;
+        if let Some(text) = self.shape()? {
+            diagram.label(node, &text);
+        }
+        if let Some(class) = self.rest().strip_prefix(":::") {
+            let length = class.find(|c: char| !(c.is_alphanumeric() || c == '_' || c == '-')).unwrap_or(class.len());
+            self.position += 3 + length;
+        }
+
+        Ok(node)
+    }
+
+    // text of a node shape like [text], (text), ((text)), {text}, >text], [(text)] or [/text/]
+    fn shape(&mut self) -> Result<Option<String>, ParseError> {
+        let rest = self.rest();
+        let open = match rest.chars().next() {
+            Some('>') => ">",
+            Some('[') | Some('(') | Some('{') => {
+                let length = rest.find(|c: char| !"[({/\\".contains(c)).unwrap_or(rest.len());
+                &rest[..length]
+            },
+            _ => return Ok(None),
+        };
+        let close = open.chars().rev().map(|c| match c {
+            '[' | '>' => ']',
+            '(' => ')',
+            '{' => '}',
+            c => c,
+        }).collect::<String>();
+
+        let body = &rest[open.len()..];
+        let (text, length) = match body.strip_prefix('"') {
+            Some(quoted) => match quoted.find('"') {
+                Some(end) if slanted_prefix(&quoted[end + 1..], &close) => (&quoted[..end], end + 2),
+                _ => return self.error("unterminated node text"),
+            },
+            None => match (0..body.len()).filter(|&i| body.is_char_boundary(i)).find(|&i| slanted_prefix(&body[i..], &close)) {
+                Some(end) => (&body[..end], end),
+                None => return self.error("unterminated node text"),
+            },
+        };
+
+        self.position += open.len() + length + close.len();
+        Ok(Some(text_of(text)))
+    }
+
+    fn link(&mut self) -> Result<Link, ParseError> {
+        let with_text = Regex::new(r"^(<)?(--|==|-\.)\s+(.*?)\s*(-{2,}|={2,}|\.-)([>xo])?").unwrap();
+        let plain = Regex::new(r"^(<|x|o)?(-{2,}|={2,}|-\.+-|~~~)([>xo])?(?:\|([^|]*)\|)?").unwrap();
+
+        let rest = self.rest();
+        let (length, both, text, head, invisible) = if let Some(caps) = with_text.captures(rest) {
+            (caps[0].len(), caps.get(1).is_some(), caps.get(3), caps.get(5), false)
+        } else if let Some(caps) = plain.captures(rest) {
+            (caps[0].len(), caps.get(1).is_some(), caps.get(4), caps.get(3), &caps[2] == "~~~")
+        } else {
+            return self.error("expected a link");
+        };
+
+        self.position += length;
+        Ok(Link {
+            label: text.map(|text| text_of(text.as_str())).filter(|text| !text.is_empty()),
+            arrow: head.is_some(),
+            both,
+            invisible,
+        })
+    }
+}
+
+// slashes of trapezoid shapes may lean both ways
+fn slanted_prefix(s: &str, close: &str) -> bool {
+    s.len() >= close.len() && s.chars().zip(close.chars()).all(|(a, b)| {
+        a == b || ("/\\".contains(a) && "/\\".contains(b))
+    })
+}
+
+fn text_of(s: &str) -> String {
+    let s = s.trim().trim_matches('"');
+    let line_break = Regex::new(r"<br\s*/?>").unwrap();
+    line_break.replace_all(s, "\n").into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn connections(gh: &LineGH) -> Vec<(usize, usize, Option<String>)> {
+        gh.connections().map(|(from, c)| (from, c.to, c.label.clone())).collect()
+    }
+
+    #[test]
+    fn test_parse_flowchart() {
+        let gh = parse("
+            graph LR
+                %% a comment
+                A[Hard edge] -->|Link text| B(Round edge)
+                B --> C{Decision}
+                C -->|One| D[Result one]
+                C -- Two --> E((Result<br>two))
+                E --- A
+        ").unwrap();
+
+        assert_eq!(gh.boxes(), &["Hard edge", "Round edge", "Decision", "Result one", "Result\ntwo"]);
+        assert_eq!(gh.pane_settings.connection_type, ConnectorType::Arrow);
+        assert_eq!(connections(&gh), vec![
+            (0, 1, Some(String::from("Link text"))),
+            (1, 2, None),
+            (2, 3, Some(String::from("One"))),
+            (2, 4, Some(String::from("Two"))),
+            (4, 0, None),
+        ]);
+    }
+
+    #[test]
+    fn test_parse_chains_and_groups() {
+        let gh = parse("flowchart TD\n  a --- b & c --- d; d:::important ~~~ a\n  subgraph one\n  e[(Database)] <--> f[/\"Slanted [box]\"\\]\n  end").unwrap();
+
+        assert_eq!(gh.boxes(), &["a", "b", "c", "d", "Database", "Slanted [box]"]);
+        assert_eq!(connections(&gh), vec![
+            (0, 1, None),
+            (0, 2, None),
+            (1, 3, None),
+            (2, 3, None),
+            (4, 5, None),
+            (5, 4, None),
+        ]);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(parse("sequenceDiagram\nA->>B: hi").unwrap_err(), ParseError::new(1, "expected graph or flowchart"));
+        assert_eq!(parse("graph LR\nA --> ").unwrap_err(), ParseError::new(2, "expected a node"));
+        assert_eq!(parse("graph LR\n\nA[open --> B").unwrap_err(), ParseError::new(3, "unterminated node text"));
+        assert_eq!(parse("graph LR\nA B").unwrap_err(), ParseError::new(2, "expected a link"));
+    }
+
+    #[test]
+    fn test_markdown_blocks() {
+        let readme = "# Title\n\n```mermaid\ngraph LR\n  A --> B\n```\n\n```rust\nfn main() {}\n```\n```mermaid\ngraph TD\n  C\n```\n";
+
+        assert_eq!(blocks(readme), vec!["graph LR\n  A --> B", "graph TD\n  C"]);
+        assert_eq!(parse(readme).unwrap().boxes(), &["A", "B"]);
+    }
+}
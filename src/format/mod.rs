@@ -0,0 +1,213 @@
+use std::collections::BTreeMap;
+
+use graph::Weight;
+
+use crate::{line_gh::LineGH, pane::ConnectorType};
+
+pub mod adjacency;
+pub mod dot;
+pub mod edge_list;
+pub mod map;
+pub mod mermaid;
+pub mod plantuml;
+
+// text formats a diagram can be built from or written to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Dot,
+    Mermaid,
+    PlantUml,
+    EdgeList,
+    AdjacencyList,
+    AdjacencyMatrix,
+}
+
+impl Format {
+    pub fn by_name(name: &str) -> Option<Format> {
+        match name {
+            "dot" | "graphviz" => Some(Format::Dot),
+            "mermaid" => Some(Format::Mermaid),
+            "plantuml" => Some(Format::PlantUml),
+            "edgelist" => Some(Format::EdgeList),
+            "adjlist" | "adjacency" => Some(Format::AdjacencyList),
+            "csv" | "adjmatrix" => Some(Format::AdjacencyMatrix),
+            _ => None,
+        }
+    }
+
+    pub fn parse(self, s: &str) -> Result<LineGH, ParseError> {
+        match self {
+            Format::Dot => dot::parse(s),
+            Format::Mermaid => mermaid::parse(s),
+            Format::PlantUml => plantuml::parse(s),
+            Format::EdgeList => edge_list::parse(s),
+            Format::AdjacencyList => adjacency::parse_list(s),
+            Format::AdjacencyMatrix => adjacency::parse_matrix(s),
+        }
+    }
+
+    // only some of formats can be written back
+    pub fn write(self, gh: &LineGH) -> Option<String> {
+        match self {
+            Format::Dot => Some(dot::to_dot(gh)),
+            Format::EdgeList => Some(edge_list::to_edge_list(&gh.graph())),
+            Format::AdjacencyList => Some(adjacency::to_list(&gh.graph())),
+            Format::AdjacencyMatrix => Some(adjacency::to_matrix(&gh.graph())),
+            Format::Mermaid | Format::PlantUml => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl ParseError {
+    pub fn new(line: usize, message: &str) -> Self {
+        ParseError {
+            line,
+            message: message.to_owned(),
+        }
+    }
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+// Collects nodes by their names in the order they first appear, so every text format
+// turns into a diagram the same way.
+#[derive(Debug, Default)]
+pub struct DiagramBuilder {
+    nodes: Vec<String>,
+    indexes: BTreeMap<String, usize>,
+    labels: BTreeMap<usize, String>,
+    connections: Vec<(usize, usize, Weight, Option<String>)>,
+}
+
+impl DiagramBuilder {
+    pub fn new() -> Self {
+        DiagramBuilder::default()
+    }
+
+    pub fn node(&mut self, name: &str) -> usize {
+        match self.indexes.get(name) {
+            Some(&i) => i,
+            None => {
+                let i = self.nodes.len();
+                self.indexes.insert(name.to_owned(), i);
+                self.nodes.push(name.to_owned());
+                i
+            },
+        }
+    }
+
+    pub fn label(&mut self, node: usize, label: &str) {
+        self.labels.insert(node, label.to_owned());
+    }
+
+    pub fn connect(&mut self, from: usize, to: usize, weight: Weight, label: Option<String>) {
+        self.connections.push((from, to, weight, label));
+    }
+
+    // a box shows the node's label if it has one, otherwise its name
+    pub fn build(self, connection_type: ConnectorType) -> LineGH {
+        let mut gh = LineGH::new();
+        gh.pane_settings.connection_type = connection_type;
+        for (i, name) in self.nodes.iter().enumerate() {
+            match self.labels.get(&i) {
+                Some(label) => gh.add_edge(label),
+                None => gh.add_edge(name),
+            };
+        }
+        for (from, to, weight, label) in self.connections {
+            gh.connect_with(from, to, weight, label);
+        }
+
+        gh
+    }
+}
+
+// Splits a line into fields, a field may be quoted with "" standing for a quote inside of it.
+// Separators which follow each other are merged unless the fields are positional, like CSV cells.
+fn fields(line: &str, separator: fn(char) -> bool, positional: bool) -> Result<Vec<String>, &'static str> {
+    let fields = quoted_fields(line, separator, positional)?;
+    if fields.iter().any(|field| !field.tail.is_empty()) {
+        return Err("a quote is followed by a text");
+    }
+
+    Ok(fields.into_iter().map(|field| field.text).collect())
+}
+
+struct Field {
+    text: String,
+    quoted: bool,
+    // what follows the closing quote up to a separator
+    tail: String,
+}
+
+fn quoted_fields(line: &str, separator: fn(char) -> bool, positional: bool) -> Result<Vec<Field>, &'static str> {
+    let mut fields = Vec::new();
+    let mut chars = line.chars().peekable();
+    loop {
+        if !positional {
+            while chars.peek().is_some_and(|&c| separator(c)) {
+                chars.next();
+            }
+            if chars.peek().is_none() {
+                return Ok(fields);
+            }
+        }
+
+        let mut field = String::new();
+        let mut tail = String::new();
+        let quoted = chars.peek() == Some(&'"');
+        if quoted {
+            chars.next();
+            loop {
+                match chars.next() {
+                    Some('"') if chars.peek() == Some(&'"') => {
+                        chars.next();
+                        field.push('"');
+                    },
+                    Some('"') => break,
+                    Some(c) => field.push(c),
+                    None => return Err("unterminated quote"),
+                }
+            }
+            while let Some(&c) = chars.peek().filter(|&&c| !separator(c)) {
+                tail.push(c);
+                chars.next();
+            }
+        } else {
+            while let Some(&c) = chars.peek().filter(|&&c| !separator(c)) {
+                field.push(c);
+                chars.next();
+            }
+        }
+        fields.push(Field { text: field, quoted, tail });
+
+        if chars.next().is_none() {
+            return Ok(fields);
+        }
+    }
+}
+
+fn quote_field(field: &str, separator: fn(char) -> bool) -> String {
+    // `=` puts a weight after a name in adjacency lists
+    if field.is_empty() || field.contains('"') || field.contains('=') || field.chars().any(separator) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_owned()
+    }
+}
+
+fn weight(line: usize, s: &str) -> Result<Weight, ParseError> {
+    s.parse().map_err(|_| ParseError::new(line, &format!("{} isn't a weight", s)))
+}
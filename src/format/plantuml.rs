@@ -0,0 +1,156 @@
+use regex::Regex;
+
+use super::{DiagramBuilder, ParseError};
+use crate::{line_gh::LineGH, pane::ConnectorType};
+
+const ELEMENT: &str = r#"\[[^\]]+\]|\([^)]+\)|:[^:]+:|"[^"]+"|[\w.]+"#;
+const KINDS: &str = "actor|agent|artifact|boundary|card|cloud|collections|component|control|database|entity|file|folder|frame|interface|node|package|person|queue|rectangle|stack|storage|usecase";
+
+// Builds a diagram from the component and use case subset of PlantUML: element declarations
+// with aliases and arrows with labels. Lines which aren't supported, like skinparams,
+// notes or activity steps, are skipped.
+pub fn parse(s: &str) -> Result<LineGH, ParseError> {
+    let arrow = Regex::new(&format!(
+        r"^(?P<from>{e})\s*(?P<arrow>[<*o#x]?[-.=]+(?:\[[^\]]*\])?(?:left|right|up|down|l|r|u|d)?[-.=]*[>*o#x]?)\s*(?P<to>{e})\s*(?::\s*(?P<label>.*))?$",
+        e = ELEMENT,
+    )).unwrap();
+    let declaration = Regex::new(&format!(
+        r"^(?:(?P<kind>{k})\s+)?(?P<name>{e})(?:\s+as\s+(?P<alias>[\w.]+))?(?:\s*<<[^>]*>>)?(?:\s*#\w+)?\s*\{{?$",
+        k = KINDS,
+        e = ELEMENT,
+    )).unwrap();
+
+    let mut diagram = DiagramBuilder::new();
+    let mut arrows = false;
+    let mut started = false;
+    let mut in_note = false;
+    let mut in_comment = false;
+    for (i, line) in s.lines().enumerate() {
+        let line = line.trim();
+        if in_comment {
+            in_comment = !line.ends_with("'/");
+            continue;
+        }
+        if in_note {
+            in_note = !line.starts_with("end note");
+            continue;
+        }
+
+        if line.starts_with("@startuml") {
+            started = true;
+        } else if line.starts_with("@enduml") {
+            break;
+        } else if line.starts_with("/'") {
+            in_comment = !line.ends_with("'/");
+        } else if line.starts_with("note ") && !line.contains(':') {
+            in_note = true;
+        } else if let Some(caps) = arrow.captures(line) {
+            let (from, to) = (element(&mut diagram, &caps["from"]), element(&mut diagram, &caps["to"]));
+            let head = &caps["arrow"];
+            let label = caps.name("label").map(|label| label.as_str().trim().replace("\\n", "\n"));
+            let (forward, backward) = (head.ends_with('>'), head.starts_with('<'));
+            if backward && !forward {
+                diagram.connect(to, from, 1, label);
+            } else {
+                diagram.connect(from, to, 1, label.clone());
+                if backward {
+                    diagram.connect(to, from, 1, label);
+                }
+            }
+
+            arrows |= forward || backward;
+        } else if let Some(caps) = declaration.captures(line) {
+            let bracketed = !caps["name"].chars().all(|c| c.is_alphanumeric() || c == '_' || c == '.');
+            if caps.name("kind").is_none() && caps.name("alias").is_none() && !bracketed {
+                continue;
+            }
+
+            let text = text_of(&caps["name"]);
+            match caps.name("alias") {
+                Some(alias) => {
+                    let node = diagram.node(alias.as_str());
+                    diagram.label(node, &text);
+                },
+                None => {
+                    diagram.node(&text);
+                },
+            }
+        } else if line.starts_with('@') {
+            return Err(ParseError::new(i + 1, "unknown directive"));
+        }
+    }
+
+    if !started && s.contains("@enduml") {
+        return Err(ParseError::new(1, "expected @startuml"));
+    }
+
+    let connection_type = match arrows {
+        true => ConnectorType::Arrow,
+        false => ConnectorType::General,
+    };
+
+    Ok(diagram.build(connection_type))
+}
+
+// an element is referred by its alias or by its text
+fn element(diagram: &mut DiagramBuilder, s: &str) -> usize {
+    diagram.node(&text_of(s))
+}
+
+fn text_of(s: &str) -> String {
+    let text = match s.chars().next() {
+        Some('[') | Some('(') | Some(':') | Some('"') => &s[1..s.len() - 1],
+        _ => s,
+    };
+
+    text.trim().replace("\\n", "\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn connections(gh: &LineGH) -> Vec<(usize, usize, Option<String>)> {
+        gh.connections().map(|(from, c)| (from, c.to, c.label.clone())).collect()
+    }
+
+    #[test]
+    fn test_parse_components() {
+        let gh = parse("
+            @startuml
+            ' the server side
+            skinparam monochrome true
+            component \"Web server\" as web
+            database db <<postgres>>
+            [Load balancer] --> web : HTTP
+            web ..> db : SQL
+            db <-- [Backup]
+            note left of db
+              keeps everything
+            end note
+            @enduml
+        ").unwrap();
+
+        assert_eq!(gh.boxes(), &["Web server", "db", "Load balancer", "Backup"]);
+        assert_eq!(gh.pane_settings.connection_type, ConnectorType::Arrow);
+        assert_eq!(connections(&gh), vec![
+            (0, 1, Some(String::from("SQL"))),
+            (2, 0, Some(String::from("HTTP"))),
+            (3, 1, None),
+        ]);
+    }
+
+    #[test]
+    fn test_parse_use_cases() {
+        let gh = parse("left to right direction\n:User: -- (Log in)\n(Log in) <-[#red]-> (Sign up)\n").unwrap();
+
+        assert_eq!(gh.boxes(), &["User", "Log in", "Sign up"]);
+        assert_eq!(connections(&gh), vec![(0, 1, None), (1, 2, None), (2, 1, None)]);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(parse("@startuml\n@startgantt\n@enduml").unwrap_err(), ParseError::new(2, "unknown directive"));
+        assert_eq!(parse("A --> B\n@enduml").unwrap_err(), ParseError::new(1, "expected @startuml"));
+    }
+}
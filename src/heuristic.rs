@@ -0,0 +1,124 @@
+use graph::Weight;
+
+// An estimate of a distance between 2 cells of a matrix counted in moves.
+pub trait Heuristic: std::fmt::Debug {
+    fn estimate(&self, from: (usize, usize), to: (usize, usize)) -> f64;
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Manhattan;
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Euclidean;
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Chebyshev;
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Octile;
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Zero;
+
+impl Heuristic for Manhattan {
+    fn estimate(&self, from: (usize, usize), to: (usize, usize)) -> f64 {
+        let (dx, dy) = deltas(from, to);
+        dx + dy
+    }
+}
+
+impl Heuristic for Euclidean {
+    fn estimate(&self, from: (usize, usize), to: (usize, usize)) -> f64 {
+        let (dx, dy) = deltas(from, to);
+        (dx * dx + dy * dy).sqrt()
+    }
+}
+
+impl Heuristic for Chebyshev {
+    fn estimate(&self, from: (usize, usize), to: (usize, usize)) -> f64 {
+        let (dx, dy) = deltas(from, to);
+        dx.max(dy)
+    }
+}
+
+impl Heuristic for Octile {
+    fn estimate(&self, from: (usize, usize), to: (usize, usize)) -> f64 {
+        let (dx, dy) = deltas(from, to);
+        dx.max(dy) + (std::f64::consts::SQRT_2 - 1.0) * dx.min(dy)
+    }
+}
+
+impl Heuristic for Zero {
+    fn estimate(&self, _: (usize, usize), _: (usize, usize)) -> f64 {
+        0.0
+    }
+}
+
+// Turns an estimate in moves into an estimate in weights of links.
+//
+// A scale bigger than the lightest link makes the search faster, but
+// the heuristic may stop being admissible.
+#[derive(Debug)]
+pub struct Scaled {
+    heuristic: Box<dyn Heuristic>,
+    scale: Weight,
+}
+
+impl Scaled {
+    pub fn new(heuristic: Box<dyn Heuristic>, scale: Weight) -> Self {
+        Scaled {
+            heuristic,
+            scale,
+        }
+    }
+
+    pub fn weight(&self, from: (usize, usize), to: (usize, usize)) -> Weight {
+        self.estimate(from, to).floor() as Weight
+    }
+}
+
+impl Heuristic for Scaled {
+    fn estimate(&self, from: (usize, usize), to: (usize, usize)) -> f64 {
+        self.heuristic.estimate(from, to) * self.scale as f64
+    }
+}
+
+pub fn by_name(name: &str) -> Option<Box<dyn Heuristic>> {
+    match name {
+        "manhattan" => Some(Box::new(Manhattan)),
+        "euclid" | "euclidean" => Some(Box::new(Euclidean)),
+        "chebyshev" => Some(Box::new(Chebyshev)),
+        "octile" => Some(Box::new(Octile)),
+        "zero" => Some(Box::new(Zero)),
+        _ => None,
+    }
+}
+
+fn deltas(from: (usize, usize), to: (usize, usize)) -> (f64, f64) {
+    let delta = |lhs: usize, rhs: usize| (lhs as f64 - rhs as f64).abs();
+    (delta(from.0, to.0), delta(from.1, to.1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_estimates() {
+        let (from, to) = ((1, 5), (4, 1));
+
+        assert_eq!(Manhattan.estimate(from, to), 7.0);
+        assert_eq!(Euclidean.estimate(from, to), 5.0);
+        assert_eq!(Chebyshev.estimate(from, to), 4.0);
+        assert!((Octile.estimate(from, to) - (4.0 + 3.0 * (std::f64::consts::SQRT_2 - 1.0))).abs() < 1e-9);
+        assert_eq!(Zero.estimate(from, to), 0.0);
+    }
+
+    #[test]
+    fn test_scaled_weight() {
+        let heuristic = Scaled::new(Box::new(Octile), 10);
+
+        assert_eq!(heuristic.weight((0, 0), (1, 1)), 14);
+        assert_eq!(heuristic.weight((0, 0), (0, 3)), 30);
+    }
+}
@@ -0,0 +1,215 @@
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+use graph::{
+    algorithm::{self, SearchError, SearchResult, ShortestPaths},
+    Weight,
+};
+
+use crate::pane::MatrixPane;
+
+// Jump Point Search on a 4-connected grid.
+//
+// It prunes symmetric paths by preferring vertical moves first, so a horizontal
+// move only turns when a wall forces it. The search works only on matrices where all
+// links have the same weight, otherwise it falls back to Dijkstra's search.
+// Steps of the trace hold a jump point followed by jump points found from it.
+pub fn jump_point_search(matrix: &MatrixPane, from: usize, look: usize) -> (Vec<Vec<usize>>, Result<SearchResult, SearchError>) {
+    let weight = match uniform_weight(matrix) {
+        Some(weight) => weight,
+        None => return algorithm::dijkstra_extra(matrix.graph(), from, look),
+    };
+
+    match matrix.graph().node_by_index(from) {
+        Some(node) if node.blocked => return (Vec::new(), Err(SearchError::BlockedSource(from))),
+        Some(_) => (),
+        None => return (Vec::new(), Err(SearchError::UnknownSource(from))),
+    }
+    if matrix.graph().node_by_index(look).is_none() {
+        return (Vec::new(), Err(SearchError::UnknownTarget(look)));
+    }
+
+    let grid = Grid { matrix, goal: matrix.get_xy(look) };
+    let goal = grid.goal;
+    let h = |(x, y): (usize, usize)| manhattan((x, y), goal) * weight;
+
+    let mut jumps = ShortestPaths::default();
+    jumps.dist.insert(from, 0);
+
+    let mut open_set = BinaryHeap::new();
+    open_set.push(Reverse((h(matrix.get_xy(from)), 0, from)));
+
+    let mut iteration_info = Vec::new();
+    while let Some(Reverse((_, g_score, current))) = open_set.pop() {
+        if g_score > jumps.dist[&current] {
+            continue;
+        }
+
+        jumps.visited.insert(current);
+        if current == look {
+            break;
+        }
+
+        let position = matrix.get_xy(current);
+        let direction = jumps.prev.get(&current).map(|&parent| direction(matrix.get_xy(parent), position));
+
+        let mut step = vec![current];
+        for d in grid.successor_directions(position, direction) {
+            let jump_point = match grid.jump(position, d) {
+                Some(jump_point) => jump_point,
+                None => continue,
+            };
+            let jump_index = matrix.get_index(jump_point.0, jump_point.1).unwrap();
+            step.push(jump_index);
+
+            let tentative_g_score = g_score + manhattan(position, jump_point) * weight;
+            if jumps.dist.get(&jump_index).is_none_or(|&score| tentative_g_score < score) {
+                jumps.dist.insert(jump_index, tentative_g_score);
+                jumps.prev.insert(jump_index, current);
+                open_set.push(Reverse((tentative_g_score + h(jump_point), tentative_g_score, jump_index)));
+            }
+        }
+
+        iteration_info.push(step);
+    }
+
+    let result = jumps.search_result(from, look).map(|result| SearchResult {
+        path: fill_path(matrix, &result.path),
+        ..result
+    });
+
+    (iteration_info, result)
+}
+
+struct Grid<'a> {
+    matrix: &'a MatrixPane,
+    goal: (usize, usize),
+}
+
+impl<'a> Grid<'a> {
+    fn walkable(&self, (x, y): (isize, isize)) -> bool {
+        if x < 0 || y < 0 {
+            return false;
+        }
+
+        let (x, y) = (x as usize, y as usize);
+        let node = self.matrix.get_index(x, y).and_then(|i| self.matrix.graph().node_by_index(i));
+        match node {
+            Some(node) => !node.blocked,
+            None => false,
+        }
+    }
+
+    fn successor_directions(&self, (x, y): (usize, usize), direction: Option<(isize, isize)>) -> Vec<(isize, isize)> {
+        let (x, y) = (x as isize, y as isize);
+        match direction {
+            None => vec![(0, -1), (0, 1), (-1, 0), (1, 0)],
+            Some((0, dy)) => vec![(0, dy), (-1, 0), (1, 0)],
+            Some((dx, _)) => {
+                let mut directions = vec![(dx, 0)];
+                for &dy in &[-1, 1] {
+                    if self.is_forced((x, y), dx, dy) {
+                        directions.push((0, dy));
+                    }
+                }
+
+                directions
+            }
+        }
+    }
+
+    // a vertical neighbor of a horizontal move is forced if it can't be reached from
+    // the previous cell by a vertical move
+    fn is_forced(&self, (x, y): (isize, isize), dx: isize, dy: isize) -> bool {
+        self.walkable((x, y + dy)) && !self.walkable((x - dx, y + dy))
+    }
+
+    fn jump(&self, from: (usize, usize), (dx, dy): (isize, isize)) -> Option<(usize, usize)> {
+        let (mut x, mut y) = (from.0 as isize, from.1 as isize);
+        loop {
+            x += dx;
+            y += dy;
+
+            if !self.walkable((x, y)) {
+                return None;
+            }
+
+            let position = (x as usize, y as usize);
+            if position == self.goal {
+                return Some(position);
+            }
+
+            if dx != 0 {
+                if self.is_forced((x, y), dx, -1) || self.is_forced((x, y), dx, 1) {
+                    return Some(position);
+                }
+            } else if self.jump(position, (-1, 0)).is_some() || self.jump(position, (1, 0)).is_some() {
+                return Some(position);
+            }
+        }
+    }
+}
+
+fn uniform_weight(matrix: &MatrixPane) -> Option<Weight> {
+    let gh = matrix.graph();
+    let mut weights = gh.indices().flat_map(|i| gh.neighbors(i)).map(|(_, weight)| weight);
+    let weight = weights.next()?;
+    if weight > 0 && weights.all(|w| w == weight) {
+        Some(weight)
+    } else {
+        None
+    }
+}
+
+fn fill_path(matrix: &MatrixPane, jump_points: &[usize]) -> Vec<usize> {
+    let mut path = jump_points.iter().take(1).cloned().collect::<Vec<usize>>();
+    for pair in jump_points.windows(2) {
+        let (mut position, to) = (matrix.get_xy(pair[0]), matrix.get_xy(pair[1]));
+        let (dx, dy) = direction(position, to);
+        while position != to {
+            position = ((position.0 as isize + dx) as usize, (position.1 as isize + dy) as usize);
+            path.push(matrix.get_index(position.0, position.1).unwrap());
+        }
+    }
+
+    path
+}
+
+fn direction(from: (usize, usize), to: (usize, usize)) -> (isize, isize) {
+    let sign = |from: usize, to: usize| (to as isize - from as isize).signum();
+    (sign(from.0, to.0), sign(from.1, to.1))
+}
+
+fn manhattan(lhs: (usize, usize), rhs: (usize, usize)) -> Weight {
+    let distance = |lhs: usize, rhs: usize| (lhs as Weight - rhs as Weight).abs();
+    distance(lhs.0, rhs.0) + distance(lhs.1, rhs.1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pane::Cell;
+
+    #[test]
+    fn test_jump_point_search_matches_dijkstra() {
+        let mut matrix = MatrixPane::new(9, 7, Cell::new('.'));
+        for &blocked in &[3, 12, 21, 30, 39, 41, 42, 43, 25, 34, 52, 60] {
+            matrix.block(blocked);
+        }
+
+        for from in (0..63).filter(|&i| !matrix.graph().is_blocked(i)) {
+            for look in 0..63 {
+                let expected = algorithm::dijkstra(matrix.graph(), from, look).map(|result| result.cost);
+                let (_, result) = jump_point_search(&matrix, from, look);
+                let result = result.map(|result| {
+                    assert_eq!(result.path.first(), Some(&from));
+                    assert_eq!(result.path.last(), Some(&look));
+                    assert_eq!(result.path.len() as Weight, result.cost / 10 + 1);
+                    result.cost
+                });
+
+                assert_eq!(result, expected, "{} -> {}", from, look);
+            }
+        }
+    }
+}
@@ -1,6 +1,12 @@
 pub mod pane;
 pub mod line_gh;
+pub mod ordering;
 pub mod path_matrix;
 pub mod animated_path;
+pub mod jump_point;
+pub mod heuristic;
+pub mod storage;
+pub mod format;
+pub mod cli;
 
 pub use line_gh as line_graph;
\ No newline at end of file
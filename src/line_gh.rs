@@ -2,11 +2,20 @@
 use std::collections::BTreeMap;
 use std::iter::FromIterator;
 
-use crate::pane::{self, Surface};
+use graph::{
+    algorithm::{self, Topology},
+    Graph,
+    Weight,
+};
 
+use serde::{Serialize, Deserialize};
+
+use crate::{ordering, pane::{self, Surface}};
+
+#[derive(Debug, Serialize, Deserialize)]
 pub struct LineGH {
     // might use here real graph?
-    vertices: BTreeMap<usize, Vec<usize>>,
+    vertices: BTreeMap<usize, Vec<Connection>>,
     edges: Vec<String>,
     pub pane_settings: pane::PaneSettings,
 }
@@ -17,6 +26,9 @@ impl LineGH {
             gap_size: 1,
             connection_size: 1,
             connection_type: pane::ConnectorType::General,
+            box_order: pane::BoxOrder::Insertion,
+            glyphs: pane::GlyphStyle::Ascii,
+            routing: pane::Routing::Above,
         })
     }
 
@@ -34,8 +46,21 @@ impl LineGH {
     }
 
     pub fn connect(&mut self, e1: usize, e2: usize) {
-        self.vertices.entry(e1).or_insert_with(Vec::new).push(e2);
-        self.vertices.entry(e2).or_insert_with(Vec::new);
+        self.connect_with(e1, e2, 1, None);
+    }
+
+    pub fn connect_with(&mut self, e1: usize, e2: usize, weight: Weight, label: Option<String>) {
+        self.vertices.entry(e1).or_default().push(Connection { to: e2, weight, label });
+        self.vertices.entry(e2).or_default();
+    }
+
+    pub fn boxes(&self) -> &[String] {
+        &self.edges
+    }
+
+    // every connection together with the index of an edge it goes from
+    pub fn connections(&self) -> impl Iterator<Item = (usize, &Connection)> {
+        self.vertices.iter().flat_map(|(&from, connections)| connections.iter().map(move |c| (from, c)))
     }
 
     pub fn count_by(&self, i: usize) -> usize {
@@ -45,12 +70,40 @@ impl LineGH {
                     + self
                         .vertices
                         .values()
-                        .fold(0, |acc, ver| acc + ver.iter().filter(|&&v| v == i).count())
+                        .fold(0, |acc, ver| acc + ver.iter().filter(|c| c.to == i).count())
             }
             None => 0,
         }
     }
 
+    pub fn graph(&self) -> Graph<String> {
+        let mut gh = Graph::new();
+        let nodes = self.edges.iter().map(|e| gh.add_node(e.clone())).collect::<Vec<_>>();
+        for (node, friend) in self.connections() {
+            if let (Some(node), Some(to)) = (nodes.get(node), nodes.get(friend.to)) {
+                gh.link(*node, *to, friend.weight);
+            }
+        }
+
+        gh
+    }
+
+    pub fn topology(&self) -> Topology {
+        algorithm::topological_order(&self.graph())
+    }
+
+    // indexes of edges in the order they are drawn
+    pub fn layout(&self) -> Vec<usize> {
+        match self.pane_settings.box_order {
+            pane::BoxOrder::Insertion => (0..self.edges.len()).collect(),
+            pane::BoxOrder::Topological => self.topology().order,
+            pane::BoxOrder::Crossings => {
+                let links = self.connections().map(|(from, friend)| (from, friend.to)).collect::<Vec<(usize, usize)>>();
+                ordering::minimize_crossings(self.edges.len(), &links)
+            },
+        }
+    }
+
     pub fn structure(&self) -> BTreeMap<usize, (usize, usize)> {
         BTreeMap::from_iter(
             self.vertices.keys().cloned().
@@ -58,21 +111,61 @@ impl LineGH {
                 map(|(i, connected)| (connected.len(), self.count_by(*i) - connected.len())))
         )
     }
-} 
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Connection {
+    pub to: usize,
+    #[serde(default = "unit_weight")]
+    pub weight: Weight,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+}
+
+impl Connection {
+    // what is written on the line, the weight is shown unless it's the default one
+    pub fn caption(&self) -> Option<String> {
+        let label = self.label.as_ref().map(|label| label.replace('\n', " "));
+        match (label, self.weight) {
+            (label, 1) => label,
+            (Some(label), weight) => Some(format!("{} ({})", label, weight)),
+            (None, weight) => Some(weight.to_string()),
+        }
+    }
+}
+
+fn unit_weight() -> Weight {
+    1
+}
+
+impl Default for LineGH {
+    fn default() -> Self {
+        LineGH::new()
+    }
+}
 
 impl std::fmt::Display for LineGH {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.pane())
+    }
+}
+
+impl LineGH {
+    // connections above the row of boxes
+    pub fn pane(&self) -> pane::Pane {
         //TODO: logic with boxes should be refactored
-        let boxes = self
-            .edges
+        let layout = self.layout();
+        let positions = layout.iter().enumerate().map(|(position, &i)| (i, position)).collect::<BTreeMap<usize, usize>>();
+        let boxes = layout
             .iter()
-            .enumerate()
-            .map(|(i, s)| {
+            .map(|&i| {
+                let s = &self.edges[i];
                 let count_connected = self.count_by(i);
-                let single_box = FormatBox::new(s, 1);
+                let glyphs = self.pane_settings.glyphs;
+                let single_box = FormatBox::new(s, 1, glyphs);
                 let max_on_line = f64::ceil(single_box.line_lenght() as f64 / self.pane_settings.connection_size as f64) as usize;
                 if count_connected > max_on_line {
-                    FormatBox::new(s, (count_connected - max_on_line) * self.pane_settings.connection_size + 1)
+                    FormatBox::new(s, (count_connected - max_on_line) * self.pane_settings.connection_size + 1, glyphs)
                 } else {
                     single_box
                 }
@@ -85,57 +178,61 @@ impl std::fmt::Display for LineGH {
             .collect::<Vec<usize>>();
         let mut pane = pane::ConnectedPane::new(&boxes_length, self.pane_settings.clone());
 
-        for (node, friends) in &self.vertices {
-            for friend in friends {
-                pane.connect(*node, *friend);
+        for (node, friend) in self.connections() {
+            if let (Some(node), Some(to)) = (positions.get(&node), positions.get(&friend.to)) {
+                pane.connect_with(*node, *to, friend.caption());
             }
         }
 
-        writeln!(f, "{}", pane.pane())?;
-
         let str_boxes = boxes.iter().map(String::from).collect::<Vec<String>>();
         let boxed_edges = flatten_line(
             &str_boxes.iter().map(|b| b.as_ref()).collect::<Vec<&str>>(),
-            self.pane_settings.gap_size
+            pane.gap_size()
         );
-        write!(f, "{}", boxed_edges)?;
-        Ok(())
-    }
-}
 
-fn new_line(index: usize, count_in: usize, count_out: usize) -> String {
-    format!("{} - in {} out {}", index, count_in, count_out)
+        let mut lines = vec![pane.pane()];
+        lines.extend(boxed_edges.lines().map(|line| pane::StrPane::new(line).pane()));
+        lines.push(pane.below_pane());
+        pane::ColumnFittablePane::new(lines).pane()
+    }
 }
 
 pub struct FormatBox<'a> {
     message: &'a str,
     tab_size: usize,
+    glyphs: pane::GlyphStyle,
 }
 
 impl<'a> FormatBox<'a> {
-    pub fn new(s: &'a str, tab_size: usize) -> Self {
+    pub fn new(s: &'a str, tab_size: usize, glyphs: pane::GlyphStyle) -> Self {
         FormatBox {
             message: s,
             tab_size,
+            glyphs,
         }
     }
 
     fn line_lenght(&self) -> usize {
-        2 + self.tab_size * 2 + size_biggest_line(&self.message)
+        2 + self.tab_size * 2 + size_biggest_line(self.message)
     }
 }
 
 impl<'a> std::fmt::Display for FormatBox<'a> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        use pane::glyph::{UP, DOWN, LEFT, RIGHT};
+
         let horizontal_tab = " ".repeat(self.tab_size);
-        let horizontal_line = "-".repeat(self.line_lenght());
-        let vertical_space = format!("|{}|", " ".repeat(self.line_lenght() - 2));
+        let horizontal = self.glyphs.horizontal().to_string().repeat(self.line_lenght() - 2);
+        let vertical = self.glyphs.vertical();
+        let top_line = format!("{}{}{}", self.glyphs.line(DOWN | RIGHT), horizontal, self.glyphs.line(DOWN | LEFT));
+        let bottom_line = format!("{}{}{}", self.glyphs.line(UP | RIGHT), horizontal, self.glyphs.line(UP | LEFT));
+        let vertical_space = format!("{}{}{}", vertical, " ".repeat(self.line_lenght() - 2), vertical);
 
-        let max_len = size_biggest_line(&self.message);
+        let max_len = size_biggest_line(self.message);
         let content = self
             .message
             .lines()
-            .map(|l| format!("|{}{: <3$}{}|", horizontal_tab, l, horizontal_tab, max_len))
+            .map(|l| format!("{4}{}{: <3$}{}{4}", horizontal_tab, l, horizontal_tab, max_len, vertical))
             .collect::<Vec<String>>()
             .join("\n");
 
@@ -152,7 +249,7 @@ impl<'a> std::fmt::Display for FormatBox<'a> {
              {}\n\
              {}\
              {}",
-            horizontal_line, vertical_space_lined, content, vertical_space_lined, horizontal_line
+            top_line, vertical_space_lined, content, vertical_space_lined, bottom_line
         )?;
         Ok(())
     }
@@ -192,12 +289,14 @@ fn flatten_line(src: &[&str], gap_size: usize) -> String {
     String::from(lines.trim())
 }
 
+// in chars, so box glyphs and labels out of ASCII take a column each
 fn size_biggest_line(s: &str) -> usize {
     s.lines().fold(
         0,
         |max, item| {
-            if item.len() > max {
-                item.len()
+            let size = item.chars().count();
+            if size > max {
+                size
             } else {
                 max
             }
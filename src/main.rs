@@ -1,26 +1,81 @@
 use regex::Regex;
-use std::io::{self, BufRead, Write};
+use std::io::{self, BufRead, IsTerminal, Read, Write};
 
-use colored::Colorize;
 use rand::Rng;
 
+use graph::algorithm;
+
 use g2h::{
     pane::{self, Surface},
     line_gh as gh,
     path_matrix,
     animated_path,
+    heuristic,
+    storage,
+    format,
+    cli,
 };
 
 fn main() -> io::Result<()> {
-    let mut gh = gh::LineGH::new();
-    let mut matrix = pane::MatrixPane::new(0, 0, "");
+    let options = match cli::parse_args(std::env::args().skip(1)) {
+        Ok(options) => options,
+        Err(err) => {
+            eprintln!("{}\n\n{}", err, cli::USAGE);
+            std::process::exit(2);
+        },
+    };
+
+    let mut output: Box<dyn Write> = match &options.output {
+        Some(file) => Box::new(io::BufWriter::new(std::fs::File::create(file)?)),
+        None => Box::new(io::stdout()),
+    };
+
+    let terminal = options.output.is_none() && io::stdout().is_terminal();
+    let render = options.render.unwrap_or(if terminal { pane::Render::Ansi } else { pane::Render::Plain });
+
+    match options.mode {
+        cli::Mode::Help => writeln!(output, "{}", cli::USAGE)?,
+        cli::Mode::Render { file, format } => render_file(&mut output, &file, format, render)?,
+        cli::Mode::Session { input, commands, prompt } => {
+            let mut gh = gh::LineGH::new();
+            let mut matrix = pane::MatrixPane::new(0, 0, pane::Cell::default());
+            for command in &commands {
+                matrix = handle_command(&mut output, &mut gh, matrix, parse_command(command), render)?;
+            }
+
+            match input {
+                Some(file) => {
+                    let content = match std::fs::read_to_string(&file) {
+                        Ok(content) => content,
+                        Err(err) => {
+                            eprintln!("can't read {}: {}", file, err);
+                            std::process::exit(1);
+                        },
+                    };
+                    for line in content.lines().filter(|line| !line.trim().is_empty()) {
+                        matrix = handle_command(&mut output, &mut gh, matrix, parse_command(line), render)?;
+                    }
+                },
+                None if commands.is_empty() => {
+                    let prompt = prompt && options.output.is_none() && io::stdin().is_terminal();
+                    repl(&mut output, &mut gh, matrix, prompt, render)?;
+                },
+                None => (),
+            }
+        },
+    }
+
+    output.flush()
+}
 
+fn repl<W: Write>(w: &mut W, gh: &mut gh::LineGH, mut matrix: pane::MatrixPane, prompt: bool, render: pane::Render) -> io::Result<()> {
     let command_prefix = b">>> ";
     let stdin = io::stdin();
-    let stdout = io::stdout();
     loop {
-        stdout.lock().write_all(command_prefix)?;
-        stdout.lock().flush()?;
+        if prompt {
+            w.write_all(command_prefix)?;
+        }
+        w.flush()?;
 
         let mut stdin = stdin.lock();
         let buffer = stdin.fill_buf()?;
@@ -30,14 +85,13 @@ fn main() -> io::Result<()> {
 
         let lines = buffer
             .split(u8::is_ascii_control)
-            .map(std::str::from_utf8)
-            .flatten()
+            .flat_map(std::str::from_utf8)
             .filter(|s| !s.is_empty())
             .collect::<Vec<&str>>();
 
         for line in lines {
-            let command = parse_command(&line);
-            matrix = handle_command(&mut stdout.lock(), &mut gh, matrix, command)?;
+            let command = parse_command(line);
+            matrix = handle_command(w, gh, matrix, command, render)?;
         }
 
         let len = buffer.len();
@@ -45,25 +99,72 @@ fn main() -> io::Result<()> {
     }
 }
 
+// draws a diagram of the file once, a saved session is read when there is no format
+fn render_file<W: Write>(w: &mut W, file: &str, format: Option<format::Format>, render: pane::Render) -> io::Result<()> {
+    let content = match file {
+        "-" => {
+            let mut content = String::new();
+            io::stdin().read_to_string(&mut content)?;
+            Ok(content)
+        },
+        file => std::fs::read_to_string(file),
+    };
+
+    let diagram = content.map_err(|err| err.to_string()).and_then(|content| match format {
+        Some(format) => format.parse(&content).map_err(|err| err.to_string()),
+        None => storage::load(content.as_bytes()).map(|(gh, _)| gh).map_err(|err| err.to_string()),
+    });
+
+    match diagram {
+        Ok(gh) => writeln!(w, "{}", gh.pane().render(render)),
+        Err(err) => {
+            eprintln!("can't render {}: {}", file, err);
+            std::process::exit(1);
+        },
+    }
+}
+
 #[derive(Debug)]
 enum Command {
     Print,
     SetGHType,
+    SetBoxOrder(pane::BoxOrder),
+    SetGlyphs(pane::GlyphStyle),
+    SetRouting(pane::Routing),
     SetGap(usize),
     SetConnectionSize(usize),
     Structure,
-    AddEdge(Box<String>),
-    ConnectEdges(usize, usize),
+    Save(String),
+    Load(String),
+    Import(format::Format, String),
+    Export(format::Format, String),
+    AddEdge(String),
+    ConnectEdges(usize, usize, graph::Weight, Option<String>),
     MatrixInit(usize, usize),
+    MatrixLoad(String),
     RandomMatrixInit(usize, usize),
     MatrixPrint,
-    MatrixSearch(usize, usize, path_matrix::PathFunc),
-    MatrixSearchAnimated(usize, usize),
-    MatrixSetWeight(usize, usize, usize),
+    MatrixDistances,
+    MatrixFlow(usize, usize),
+    MatrixComponents(bool),
+    Components(bool),
+    Cycles,
+    MatrixSpanningTree(path_matrix::TreeFunc),
+    Distances,
+    MatrixSearch(usize, usize, path_matrix::PathFunc, HeuristicOptions),
+    MatrixSearchAlternatives(usize, usize, usize),
+    MatrixSearchAnimated(usize, usize, path_matrix::PathFunc, HeuristicOptions),
+    MatrixSetWeight(usize, usize, graph::Weight),
     MatrixBlockVertices(usize),
+    MatrixUnblockVertices(usize),
     StructureMatrix,
+    // a known command with an argument which can't be used, the message tells why
+    Invalid(String),
 }
 
+// a heuristic and its scale, by default the scale is the lightest link of a matrix
+type HeuristicOptions = (Box<dyn heuristic::Heuristic>, Option<graph::Weight>);
+
 fn parse_command(line: &str) -> Option<Command> {
     let clean_line = line.trim();
 
@@ -71,6 +172,20 @@ fn parse_command(line: &str) -> Option<Command> {
         Some(Command::Print)
     } else if clean_line.starts_with("structure") {
         Some(Command::Structure)
+    } else if let Some(file) = clean_line.strip_prefix("save ") {
+        Some(Command::Save(file.trim().to_owned()))
+    } else if let Some(file) = clean_line.strip_prefix("load ") {
+        Some(Command::Load(file.trim().to_owned()))
+    } else if let Some(import) = clean_line.strip_prefix("import ") {
+        let mut args = import.trim().splitn(2, ' ');
+        let format = args.next().and_then(format::Format::by_name)?;
+        let file = args.next()?.trim();
+        Some(Command::Import(format, file.to_owned()))
+    } else if let Some(export) = clean_line.strip_prefix("export ") {
+        let mut args = export.trim().splitn(2, ' ');
+        let format = args.next().and_then(format::Format::by_name)?;
+        let file = args.next()?.trim();
+        Some(Command::Export(format, file.to_owned()))
     } else if clean_line.starts_with("settings") {
         let gap_regex = Regex::new(r"settings gap edge (?P<size>.+)").unwrap();
         let connection_size_regex = Regex::new(r"settings gap vert (?P<size>.+)").unwrap();
@@ -87,55 +202,97 @@ fn parse_command(line: &str) -> Option<Command> {
             Some(Command::SetConnectionSize(size))
         } else if clean_line.contains("settings related") {
             Some(Command::SetGHType)
+        } else if clean_line.contains("settings order topological") {
+            Some(Command::SetBoxOrder(pane::BoxOrder::Topological))
+        } else if clean_line.contains("settings order insertion") {
+            Some(Command::SetBoxOrder(pane::BoxOrder::Insertion))
+        } else if clean_line.contains("settings order crossings") {
+            Some(Command::SetBoxOrder(pane::BoxOrder::Crossings))
+        } else if let Some(style) = clean_line.strip_prefix("settings glyphs ") {
+            pane::GlyphStyle::by_name(style.trim()).map(Command::SetGlyphs)
+        } else if let Some(routing) = clean_line.strip_prefix("settings routing ") {
+            pane::Routing::by_name(routing.trim()).map(Command::SetRouting)
         } else {
             None
         }
     } else if clean_line.starts_with("matrix") {
         let init_command = Regex::new(r"matrix (?P<weight>\d+) (?P<hight>\d+)").unwrap();
         let search_command = Regex::new(r"matrix search (?P<type>[-\w]+) (?P<from>\d+) (?P<look>\d+)").unwrap();
-        let search_animated_command = Regex::new(r"matrix search animated (?P<from>\d+) (?P<look>\d+)").unwrap();
-        let set_weight_command = Regex::new(r"matrix weight (?P<index>\d+) (?P<edge>\d+) (?P<weight>\d+)").unwrap();
+        let search_alternatives_command = Regex::new(r"matrix search k=(?P<k>\d+) (?P<from>\d+) (?P<look>\d+)").unwrap();
+        let search_animated_command = Regex::new(r"matrix search animated (?:(?P<type>[-\w]+) )?(?P<from>\d+) (?P<look>\d+)").unwrap();
+        let set_weight_command = Regex::new(r"matrix weight (?P<index>\d+) (?P<edge>\d+) (?P<weight>-?\d+)").unwrap();
         let block_command = Regex::new(r"matrix block (?P<index>\d+)").unwrap();
+        let unblock_command = Regex::new(r"matrix unblock (?P<index>\d+)").unwrap();
+        let flow_command = Regex::new(r"matrix flow (?P<from>\d+) (?P<to>\d+)").unwrap();
         let random_command = Regex::new(r"matrix random (?P<weight>\d+) (?P<hight>\d+)").unwrap();
         let structure_command = Regex::new(r"matrix structure").unwrap();
 
-        if clean_line.contains("matrix print") {
+        if let Some(file) = clean_line.strip_prefix("matrix load ") {
+            Some(Command::MatrixLoad(file.trim().to_owned()))
+        } else if clean_line.contains("matrix print") {
             Some(Command::MatrixPrint)
+        } else if clean_line.contains("matrix distances") {
+            Some(Command::MatrixDistances)
+        } else if clean_line.starts_with("matrix components") {
+            Some(Command::MatrixComponents(clean_line.contains("strong")))
+        } else if clean_line.starts_with("matrix mst") {
+            if clean_line.contains("kruskal") {
+                Some(Command::MatrixSpanningTree(path_matrix::TreeFunc::Kruskal))
+            } else {
+                Some(Command::MatrixSpanningTree(path_matrix::TreeFunc::Prim))
+            }
         } else if init_command.is_match(clean_line) {
             let caps = init_command.captures(clean_line).unwrap();
             let w = caps["weight"].parse().unwrap();
             let h = caps["hight"].parse().unwrap();
             Some(Command::MatrixInit(w, h))
-        } else if search_command.is_match(clean_line) {
-            let caps = search_command.captures(clean_line).unwrap();
-            let path_func = &caps["type"];
-            let w = caps["from"].parse().unwrap();
-            let h = caps["look"].parse().unwrap();
-
-            let path_func = if path_func  == "djikstra" {
-                path_matrix::PathFunc::Dijkstra
-            } else if path_func == "a-star" {
-                path_matrix::PathFunc::AStar
+        } else if search_alternatives_command.is_match(clean_line) {
+            let caps = search_alternatives_command.captures(clean_line).unwrap();
+            let k = caps["k"].parse().ok()?;
+            let w = caps["from"].parse().ok()?;
+            let h = caps["look"].parse().ok()?;
+            if k == 0 {
+                Some(Command::Invalid(String::from("k should be at least 1")))
             } else {
-                path_matrix::PathFunc::Dijkstra
-            };
-
-            Some(Command::MatrixSearch(w, h, path_func))
+                Some(Command::MatrixSearchAlternatives(w, h, k))
+            }
         } else if search_animated_command.is_match(clean_line) {
             let caps = search_animated_command.captures(clean_line).unwrap();
-            let w = caps["from"].parse().unwrap();
-            let h = caps["look"].parse().unwrap();
-            Some(Command::MatrixSearchAnimated(w, h))
+            let path_func = parse_path_func(caps.name("type").map_or("", |t| t.as_str()));
+            let w = caps["from"].parse().ok()?;
+            let h = caps["look"].parse().ok()?;
+            match parse_heuristic(clean_line) {
+                Ok(heuristic) => Some(Command::MatrixSearchAnimated(w, h, path_func, heuristic)),
+                Err(message) => Some(Command::Invalid(message)),
+            }
+        } else if search_command.is_match(clean_line) {
+            let caps = search_command.captures(clean_line).unwrap();
+            let path_func = parse_path_func(&caps["type"]);
+            let w = caps["from"].parse().ok()?;
+            let h = caps["look"].parse().ok()?;
+            match parse_heuristic(clean_line) {
+                Ok(heuristic) => Some(Command::MatrixSearch(w, h, path_func, heuristic)),
+                Err(message) => Some(Command::Invalid(message)),
+            }
         } else if set_weight_command.is_match(clean_line) {
             let caps = set_weight_command.captures(clean_line).unwrap();
-            let index = caps["index"].parse().unwrap();
-            let edge = caps["edge"].parse().unwrap();
-            let weight = caps["weight"].parse().unwrap();
+            let index = caps["index"].parse().ok()?;
+            let edge = caps["edge"].parse().ok()?;
+            let weight = caps["weight"].parse().ok()?;
             Some(Command::MatrixSetWeight(index, edge, weight))
+        } else if flow_command.is_match(clean_line) {
+            let caps = flow_command.captures(clean_line).unwrap();
+            let from = caps["from"].parse().unwrap();
+            let to = caps["to"].parse().unwrap();
+            Some(Command::MatrixFlow(from, to))
         } else if block_command.is_match(clean_line) {
             let caps = block_command.captures(clean_line).unwrap();
             let index = caps["index"].parse().unwrap();
             Some(Command::MatrixBlockVertices(index))
+        } else if unblock_command.is_match(clean_line) {
+            let caps = unblock_command.captures(clean_line).unwrap();
+            let index = caps["index"].parse().unwrap();
+            Some(Command::MatrixUnblockVertices(index))
         } else if random_command.is_match(clean_line) {
             let caps = random_command.captures(clean_line).unwrap();
             let w = caps["weight"].parse().unwrap();
@@ -149,42 +306,157 @@ fn parse_command(line: &str) -> Option<Command> {
     } else {
         let add_edge_command = Regex::new(r"edge add (?P<data>.+)").unwrap();
         let add_verticale_command =
-            Regex::new(r"edge connect (?P<first>\d+) (?P<second>\d+)").unwrap();
+            Regex::new(r"edge connect (?P<first>\d+) (?P<second>\d+)(?: weight (?P<weight>-?\d+))?(?: label (?P<label>.+))?").unwrap();
 
         if add_edge_command.is_match(clean_line) {
             let caps = add_edge_command.captures(clean_line).unwrap();
-            Some(Command::AddEdge(Box::new(String::from(&caps["data"]))))
+            Some(Command::AddEdge(String::from(&caps["data"])))
         } else if add_verticale_command.is_match(clean_line) {
             let caps = add_verticale_command.captures(clean_line).unwrap();
-            let first = caps["first"].parse().unwrap();
-            let second = caps["second"].parse().unwrap();
+            let first = caps["first"].parse().ok()?;
+            let second = caps["second"].parse().ok()?;
+            let weight = match caps.name("weight") {
+                Some(weight) => weight.as_str().parse().ok()?,
+                None => 1,
+            };
+            let label = caps.name("label").map(|label| label.as_str().trim().to_owned());
 
-            Some(Command::ConnectEdges(first, second))
+            Some(Command::ConnectEdges(first, second, weight, label))
+        } else if clean_line.starts_with("edge distances") {
+            Some(Command::Distances)
+        } else if clean_line.starts_with("edge cycles") {
+            Some(Command::Cycles)
+        } else if clean_line.starts_with("edge components") {
+            Some(Command::Components(clean_line.contains("strong")))
         } else {
             None
         }
     }
 }
 
+fn parse_path_func(path_func: &str) -> path_matrix::PathFunc {
+    if path_func  == "djikstra" {
+        path_matrix::PathFunc::Dijkstra
+    } else if path_func == "a-star" {
+        path_matrix::PathFunc::AStar
+    } else if path_func == "bellman-ford" {
+        path_matrix::PathFunc::BellmanFord
+    } else if path_func == "jps" {
+        path_matrix::PathFunc::JumpPoint
+    } else if path_func == "bidir" {
+        path_matrix::PathFunc::Bidirectional
+    } else {
+        path_matrix::PathFunc::Dijkstra
+    }
+}
+
+fn parse_heuristic(line: &str) -> Result<HeuristicOptions, String> {
+    let heuristic_option = Regex::new(r"heuristic=(?P<name>\w+)").unwrap();
+    let scale_option = Regex::new(r"scale=(?P<scale>\d+)").unwrap();
+
+    let heuristic = match heuristic_option.captures(line) {
+        Some(caps) => heuristic::by_name(&caps["name"]).ok_or_else(|| format!("unknown heuristic {}", &caps["name"]))?,
+        None => Box::new(heuristic::Manhattan),
+    };
+    let scale = match scale_option.captures(line) {
+        Some(caps) => Some(caps["scale"].parse().map_err(|_| format!("scale {} is too large", &caps["scale"]))?),
+        None => None,
+    };
+
+    Ok((heuristic, scale))
+}
+
 fn handle_command<W: Write>(
     w: &mut W,
     gh: &mut gh::LineGH,
     mut matrix:  pane::MatrixPane,
     command: Option<Command>,
+    render: pane::Render,
 ) -> io::Result<pane::MatrixPane> {
     match command {
         Some(Command::Print) => {
-            writeln!(w, "{}", gh)?;
+            writeln!(w, "{}", gh.pane().render(render))?;
+            if gh.pane_settings.box_order == pane::BoxOrder::Topological {
+                write_cycles(w, &gh.topology().cycles)?;
+            }
+        },
+        Some(Command::Cycles) => {
+            write_cycles(w, &gh.topology().cycles)?;
         },
         Some(Command::Structure) => {},
+        Some(Command::Save(file)) => {
+            let saved = std::fs::File::create(&file).and_then(|f| storage::save(io::BufWriter::new(f), gh, &matrix));
+            if let Err(err) = saved {
+                writeln!(w, "can't save {}: {}", file, err)?;
+            }
+        },
+        Some(Command::Load(file)) => {
+            match std::fs::File::open(&file).and_then(|f| storage::load(io::BufReader::new(f))) {
+                Ok((diagram, loaded)) => {
+                    *gh = diagram;
+                    matrix = loaded;
+                },
+                Err(err) => writeln!(w, "can't load {}: {}", file, err)?,
+            }
+        },
+        Some(Command::Import(format, file)) => {
+            match std::fs::read_to_string(&file) {
+                Ok(content) => match format.parse(&content) {
+                    Ok(diagram) => *gh = diagram,
+                    Err(err) => writeln!(w, "can't import {}: {}", file, err)?,
+                },
+                Err(err) => writeln!(w, "can't import {}: {}", file, err)?,
+            }
+        },
+        Some(Command::MatrixLoad(file)) => {
+            match std::fs::read_to_string(&file) {
+                Ok(content) => match format::map::parse(&content, mark(pane::Color::Black)) {
+                    Ok(loaded) => matrix = loaded,
+                    Err(err) => writeln!(w, "can't load {}: {}", file, err)?,
+                },
+                Err(err) => writeln!(w, "can't load {}: {}", file, err)?,
+            }
+        },
+        Some(Command::Export(format, file)) => match format.write(gh) {
+            Some(content) => {
+                if let Err(err) = std::fs::write(&file, content) {
+                    writeln!(w, "can't export {}: {}", file, err)?;
+                }
+            },
+            None => writeln!(w, "can't export {}: the format is read only", file)?,
+        },
         Some(Command::AddEdge(data)) => {
             gh.add_edge(&data);
         },
-        Some(Command::ConnectEdges(from, to)) => {
-            gh.connect(from, to);
+        Some(Command::ConnectEdges(from, to, weight, label)) => {
+            gh.connect_with(from, to, weight, label);
+        },
+        Some(Command::Distances) => {
+            match algorithm::floyd_warshall(&gh.graph()) {
+                Ok(table) => writeln!(w, "{}", pane::DistancePane::new(&table).pane().render(render))?,
+                Err(err) => writeln!(w, "no distances: {}", err)?,
+            }
+        },
+        Some(Command::Components(strong)) => {
+            let graph = gh.graph();
+            let components = if strong {
+                algorithm::strongly_connected_components(&graph)
+            } else {
+                algorithm::connected_components(&graph)
+            };
+
+            for component in components {
+                let edges = component.iter()
+                    .map(|&i| format!("{} {}", i, graph.node_by_index(i).unwrap().data))
+                    .collect::<Vec<String>>();
+                writeln!(w, "{}", edges.join(", "))?;
+            }
         },
         Some(Command::SetGap(size)) => { gh.pane_settings.gap_size = size },
         Some(Command::SetConnectionSize(size)) => { gh.pane_settings.connection_size = size },
+        Some(Command::SetBoxOrder(order)) => { gh.pane_settings.box_order = order },
+        Some(Command::SetGlyphs(glyphs)) => { gh.pane_settings.glyphs = glyphs },
+        Some(Command::SetRouting(routing)) => { gh.pane_settings.routing = routing },
         Some(Command::SetGHType) => { 
             if gh.pane_settings.connection_type == pane::ConnectorType::General {
                 gh.pane_settings.connection_type = pane::ConnectorType::Arrow;
@@ -193,55 +465,118 @@ fn handle_command<W: Write>(
             }
         },
         Some(Command::MatrixInit(w, h)) => {
-            matrix = pane::MatrixPane::new(w, h, &"▅".black().to_string());
+            matrix = pane::MatrixPane::new(w, h, mark(pane::Color::Black));
         },
         Some(Command::MatrixPrint) => {
-            writeln!(w, "{}", matrix.pane())?;
+            writeln!(w, "{}", matrix.pane().render(render))?;
         }
-        Some(Command::MatrixSearch(from, look, path_func)) => {
-            matrix = path_matrix::construct_path(matrix, from, look, &"▅".red().to_string(), &"▅".yellow().to_string(), path_func);
-            writeln!(w, "{}", matrix.pane())?;
+        Some(Command::MatrixDistances) => {
+            match algorithm::floyd_warshall(matrix.graph()) {
+                Ok(table) => writeln!(w, "{}", pane::DistancePane::new(&table).pane().render(render))?,
+                Err(err) => writeln!(w, "no distances: {}", err)?,
+            }
+        },
+        Some(Command::MatrixComponents(strong)) => {
+            let components = if strong {
+                algorithm::strongly_connected_components(matrix.graph())
+            } else {
+                algorithm::connected_components(matrix.graph())
+            };
+
+            path_matrix::color_components(&mut matrix, &components, &palette());
+            writeln!(w, "{}", matrix.pane().render(render))?;
+            writeln!(w, "components {}", components.len())?;
             matrix.clean();
         },
-        Some(Command::MatrixSearchAnimated(from, look)) => {
-            let frames = animated_path::frames(&mut matrix, from, look, &"▅".red().to_string(), &"▅".yellow().to_string());
-            for frame in frames {
-                writeln!(w, "{}", termion::clear::BeforeCursor)?;
-                writeln!(w, "{}", frame)?;
-                std::thread::sleep(std::time::Duration::from_millis(20)); // should be a relation from a size of frames or graph?
+        Some(Command::MatrixFlow(from, to)) => {
+            match path_matrix::construct_flow(&mut matrix, from, to, mark(pane::Color::Yellow), mark(pane::Color::Red)) {
+                Ok(flow) => {
+                    writeln!(w, "{}", matrix.links_pane(&flow.saturated, &flow.cut).render(render))?;
+                    writeln!(w, "flow {}", flow.value)?;
+                    let cut = flow.cut.iter().map(|(from, to)| format!("{} -> {}", from, to)).collect::<Vec<String>>();
+                    writeln!(w, "cut {}", cut.join(", "))?;
+                },
+                Err(err) => writeln!(w, "no flow: {}", err)?,
             }
             matrix.clean();
         },
-        Some(Command::MatrixBlockVertices(index)) => {
-            if let Some(node) = matrix.get_node(index) {
-                node.borrow_mut().edges = None;
+        Some(Command::MatrixSpanningTree(tree_func)) => {
+            let (links, weight) = path_matrix::construct_spanning_tree(&mut matrix, mark(pane::Color::Red), tree_func);
+            writeln!(w, "{}", matrix.links_pane(&links, &[]).render(render))?;
+            writeln!(w, "weight {}", weight)?;
+            matrix.clean();
+        },
+        Some(Command::MatrixSearch(from, look, path_func, (h, scale))) => {
+            let heuristic = heuristic::Scaled::new(h, scale.unwrap_or_else(|| matrix.min_weight()));
+            match path_matrix::construct_path(&mut matrix, from, look, mark(pane::Color::Red), mark(pane::Color::Yellow), path_func, &heuristic) {
+                Ok(report) => {
+                    writeln!(w, "{}", matrix.pane().render(render))?;
+                    writeln!(w, "cost {}", report.result.cost)?;
+                    writeln!(w, "visited {}", report.result.visited.len())?;
+                    if let Some(admissible) = report.admissible {
+                        writeln!(w, "heuristic admissible {}", admissible)?;
+                    }
+                },
+                Err(err) => writeln!(w, "no route: {}", err)?,
             }
+            matrix.clean();
+        },
+        Some(Command::MatrixSearchAlternatives(from, look, k)) => {
+            let symbols = palette();
+            match path_matrix::construct_paths(&mut matrix, from, look, k, &symbols) {
+                Ok(results) => {
+                    writeln!(w, "{}", matrix.pane().render(render))?;
+                    for (result, symbol) in results.iter().zip(symbols.iter().cycle()) {
+                        writeln!(w, "{} cost {}", symbol.render(render), result.cost)?;
+                    }
+                },
+                Err(err) => writeln!(w, "no route: {}", err)?,
+            }
+            matrix.clean();
+        },
+        Some(Command::MatrixSearchAnimated(from, look, path_func, (h, scale))) => {
+            let heuristic = heuristic::Scaled::new(h, scale.unwrap_or_else(|| matrix.min_weight()));
+            match animated_path::frames(&mut matrix, from, look, mark(pane::Color::Red), mark(pane::Color::Yellow), path_func, &heuristic) {
+                Ok(frames) => {
+                    for frame in frames {
+                        writeln!(w, "{}", termion::clear::BeforeCursor)?;
+                        writeln!(w, "{}", frame.render(render))?;
+                        std::thread::sleep(std::time::Duration::from_millis(20)); // should be a relation from a size of frames or graph?
+                    }
+                },
+                Err(err) => writeln!(w, "no route: {}", err)?,
+            }
+            matrix.clean();
+        },
+        Some(Command::MatrixBlockVertices(index)) => {
+            matrix.block(index);
+        },
+        Some(Command::MatrixUnblockVertices(index)) => {
+            matrix.unblock(index);
         },
         Some(Command::MatrixSetWeight(index, edge_index, weight)) => {
-            if let Some(node) = matrix.get_node(index) {
-                if let Some(edges) = node.borrow_mut().edges.as_mut() {
-                    edges[edge_index].weight = weight
-                }
+            if let Some(link) = matrix.get_link(index, edge_index) {
+                link.weight = weight
             }
         },
         Some(Command::RandomMatrixInit(w, h)) => {
-            matrix = pane::MatrixPane::new(w, h, &"▅".black().to_string());
-            for node in &matrix.node_list[..matrix.node_list.len()-1] {
-                if let Some(edges) = node.borrow_mut().edges.as_mut() {
-                    if let Some(edge) = edges.get_mut(0) {
-                        edge.weight = rand::thread_rng().gen_range(0, 100);
-                    }
-                    if let Some(edge) = edges.get_mut(1) {
-                        edge.weight = rand::thread_rng().gen_range(0, 100);
+            matrix = pane::MatrixPane::new(w, h, mark(pane::Color::Black));
+            for node in 0..matrix.node_list.len()-1 {
+                for nth in 0..2 {
+                    if let Some(link) = matrix.get_link(node, nth) {
+                        link.weight = rand::thread_rng().gen_range(0, 100);
                     }
                 }
             }
         },
         Some(Command::StructureMatrix) => {
             let structure = matrix.structure().to_string();
-            let structure = gh::FormatBox::new(&structure, 1);
+            let structure = gh::FormatBox::new(&structure, 1, gh.pane_settings.glyphs);
             writeln!(w, "{}", structure)?;
         },
+        Some(Command::Invalid(message)) => {
+            writeln!(w, "{}", message)?;
+        },
         None => {
             writeln!(w, "cannot hold this type of command")?;
         },
@@ -249,3 +584,28 @@ fn handle_command<W: Write>(
 
     Ok(matrix)
 }
+
+fn mark(color: pane::Color) -> pane::Cell {
+    pane::Cell::new('▅').fg(color)
+}
+
+fn palette() -> Vec<pane::Cell> {
+    vec![
+        mark(pane::Color::Red),
+        mark(pane::Color::Green),
+        mark(pane::Color::Yellow),
+        mark(pane::Color::Blue),
+        mark(pane::Color::Magenta),
+        mark(pane::Color::Cyan),
+    ]
+}
+
+fn write_cycles<W: Write>(w: &mut W, cycles: &[Vec<usize>]) -> io::Result<()> {
+    for cycle in cycles {
+        let mut nodes = cycle.iter().map(|i| i.to_string()).collect::<Vec<String>>();
+        nodes.push(cycle[0].to_string());
+        writeln!(w, "cycle {}", nodes.join(" -> "))?;
+    }
+
+    Ok(())
+}
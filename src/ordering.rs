@@ -0,0 +1,170 @@
+// Orders boxes of a row so connections drawn on one side of it cross as little as possible,
+// the total length of connections is made short among orders with the fewest crossings.
+
+// every order is tried up to this number of boxes
+const EXACT_LIMIT: usize = 8;
+const ROUNDS: usize = 32;
+
+// indices of boxes in the order they are drawn, the insertion order is kept on ties
+pub fn minimize_crossings(count: usize, links: &[(usize, usize)]) -> Vec<usize> {
+    let links = links.iter()
+        .filter(|(from, to)| from != to && *from < count && *to < count)
+        .cloned()
+        .collect::<Vec<(usize, usize)>>();
+
+    if count <= EXACT_LIMIT {
+        exact(count, &links)
+    } else {
+        let order = barycenter(count, &links);
+        sifting(order, &links)
+    }
+}
+
+// crossings and the total length of connections drawn in the order
+pub fn cost(order: &[usize], links: &[(usize, usize)]) -> (usize, usize) {
+    let mut positions = vec![0; order.len()];
+    for (position, &i) in order.iter().enumerate() {
+        positions[i] = position;
+    }
+    let spans = links.iter()
+        .filter(|(from, to)| from != to)
+        .map(|&(from, to)| (positions[from].min(positions[to]), positions[from].max(positions[to])))
+        .collect::<Vec<(usize, usize)>>();
+
+    let mut crossings = 0;
+    for (i, &(l1, r1)) in spans.iter().enumerate() {
+        for &(l2, r2) in &spans[i + 1..] {
+            if (l1 < l2 && l2 < r1 && r1 < r2) || (l2 < l1 && l1 < r2 && r2 < r1) {
+                crossings += 1;
+            }
+        }
+    }
+
+    (crossings, spans.iter().map(|(l, r)| r - l).sum())
+}
+
+fn exact(count: usize, links: &[(usize, usize)]) -> Vec<usize> {
+    let mut order = (0..count).collect::<Vec<usize>>();
+    let mut best = (cost(&order, links), order.clone());
+    while next_permutation(&mut order) {
+        let cost = cost(&order, links);
+        if cost < best.0 {
+            best = (cost, order.clone());
+        }
+    }
+
+    best.1
+}
+
+// permutations come in the lexicographic order, false is returned after the last one
+fn next_permutation(order: &mut [usize]) -> bool {
+    let pivot = match (1..order.len()).rev().find(|&i| order[i - 1] < order[i]) {
+        Some(i) => i - 1,
+        None => return false,
+    };
+    let successor = (pivot + 1..order.len()).rev().find(|&i| order[i] > order[pivot]).unwrap_or(pivot);
+    order.swap(pivot, successor);
+    order[pivot + 1..].reverse();
+
+    true
+}
+
+// boxes are moved to the mean position of their neighbours until it stops helping
+fn barycenter(count: usize, links: &[(usize, usize)]) -> Vec<usize> {
+    let mut neighbours = vec![Vec::new(); count];
+    for &(from, to) in links {
+        neighbours[from].push(to);
+        neighbours[to].push(from);
+    }
+
+    let mut order = (0..count).collect::<Vec<usize>>();
+    let mut best = (cost(&order, links), order.clone());
+    for _ in 0..ROUNDS {
+        let mut positions = vec![0.0; count];
+        for (position, &i) in order.iter().enumerate() {
+            positions[i] = position as f64;
+        }
+        let centers = (0..count).map(|i| match neighbours[i].len() {
+            0 => positions[i],
+            n => neighbours[i].iter().map(|&j| positions[j]).sum::<f64>() / n as f64,
+        }).collect::<Vec<f64>>();
+
+        let mut next = order.clone();
+        next.sort_by(|&a, &b| centers[a].partial_cmp(&centers[b]).unwrap_or(std::cmp::Ordering::Equal));
+        if next == order {
+            break;
+        }
+
+        order = next;
+        let cost = cost(&order, links);
+        if cost < best.0 {
+            best = (cost, order.clone());
+        }
+    }
+
+    best.1
+}
+
+// every box is moved to the position where it's best while that helps
+fn sifting(mut order: Vec<usize>, links: &[(usize, usize)]) -> Vec<usize> {
+    let mut best = cost(&order, links);
+    for _ in 0..ROUNDS {
+        let mut improved = false;
+        for i in 0..order.len() {
+            let from = order.iter().position(|&j| j == i).unwrap_or(0);
+            let mut moved = order.clone();
+            moved.remove(from);
+            for to in 0..order.len() {
+                moved.insert(to, i);
+                let cost = cost(&moved, links);
+                if cost < best {
+                    best = cost;
+                    order = moved.clone();
+                    improved = true;
+                }
+                moved.remove(to);
+            }
+        }
+        if !improved {
+            break;
+        }
+    }
+
+    order
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cost() {
+        assert_eq!(cost(&[0, 1, 2, 3], &[(0, 2), (1, 3)]), (1, 4));
+        assert_eq!(cost(&[0, 2, 1, 3], &[(0, 2), (1, 3)]), (0, 2));
+        assert_eq!(cost(&[0, 1, 2], &[(0, 2), (0, 1), (1, 1)]), (0, 3));
+    }
+
+    #[test]
+    fn test_exact_order() {
+        // a chain inserted out of order is straightened
+        let order = minimize_crossings(4, &[(0, 2), (2, 1), (1, 3)]);
+        assert_eq!(cost(&order, &[(0, 2), (2, 1), (1, 3)]), (0, 3));
+        assert_eq!(order, vec![0, 2, 1, 3]);
+
+        // nothing to improve keeps the insertion order
+        assert_eq!(minimize_crossings(3, &[(0, 1)]), vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn test_heuristic_order() {
+        // a chain of twelve boxes which jumps between both halves of the row
+        let count = 12;
+        let chain = (0..count).map(|i| (i % 2) * (count / 2) + i / 2).collect::<Vec<usize>>();
+        let links = chain.windows(2).map(|pair| (pair[0], pair[1])).collect::<Vec<(usize, usize)>>();
+
+        let before = cost(&(0..count).collect::<Vec<usize>>(), &links);
+        let after = cost(&minimize_crossings(count, &links), &links);
+        assert!(after < before);
+        assert_eq!(after.0, 0);
+    }
+}
@@ -0,0 +1,185 @@
+use serde::{Serialize, Deserialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Color {
+    Black,
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+    White,
+}
+
+impl Color {
+    fn ansi(self) -> u8 {
+        self as u8
+    }
+
+    fn html(self) -> &'static str {
+        match self {
+            Color::Black => "black",
+            Color::Red => "red",
+            Color::Green => "green",
+            Color::Yellow => "olive",
+            Color::Blue => "blue",
+            Color::Magenta => "purple",
+            Color::Cyan => "teal",
+            Color::White => "white",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct Style {
+    pub fg: Option<Color>,
+    pub bg: Option<Color>,
+    pub bold: bool,
+}
+
+impl Style {
+    fn ansi(self) -> String {
+        let mut codes = Vec::new();
+        if self.bold {
+            codes.push(String::from("1"));
+        }
+        if let Some(fg) = self.fg {
+            codes.push((30 + fg.ansi()).to_string());
+        }
+        if let Some(bg) = self.bg {
+            codes.push((40 + bg.ansi()).to_string());
+        }
+
+        format!("\x1b[{}m", codes.join(";"))
+    }
+
+    fn html(self) -> String {
+        let mut rules = Vec::new();
+        if let Some(fg) = self.fg {
+            rules.push(format!("color:{}", fg.html()));
+        }
+        if let Some(bg) = self.bg {
+            rules.push(format!("background-color:{}", bg.html()));
+        }
+        if self.bold {
+            rules.push(String::from("font-weight:bold"));
+        }
+
+        rules.join(";")
+    }
+}
+
+// a character of a pane and the way it's shown
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct Cell {
+    pub glyph: char,
+    #[serde(default)]
+    pub style: Style,
+}
+
+impl Cell {
+    pub fn new(glyph: char) -> Self {
+        Cell {
+            glyph,
+            style: Style::default(),
+        }
+    }
+
+    pub fn fg(mut self, color: Color) -> Self {
+        self.style.fg = Some(color);
+        self
+    }
+
+    pub fn bg(mut self, color: Color) -> Self {
+        self.style.bg = Some(color);
+        self
+    }
+
+    pub fn bold(mut self) -> Self {
+        self.style.bold = true;
+        self
+    }
+
+    pub fn render(self, render: Render) -> String {
+        render.line(&[self])
+    }
+}
+
+impl Default for Cell {
+    fn default() -> Self {
+        Cell::new(' ')
+    }
+}
+
+impl From<char> for Cell {
+    fn from(glyph: char) -> Self {
+        Cell::new(glyph)
+    }
+}
+
+impl std::fmt::Display for Cell {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.render(Render::Ansi))
+    }
+}
+
+// the way styles of cells are shown at display time
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Render {
+    Ansi,
+    Plain,
+    Html,
+}
+
+impl Render {
+    pub fn by_name(name: &str) -> Option<Render> {
+        match name {
+            "ansi" => Some(Render::Ansi),
+            "plain" => Some(Render::Plain),
+            "html" => Some(Render::Html),
+            _ => None,
+        }
+    }
+
+    // cells of the same style are written together
+    pub fn line(self, cells: &[Cell]) -> String {
+        let mut line = String::new();
+        for run in cells.chunk_by(|a, b| a.style == b.style) {
+            let style = run[0].style;
+            let text = run.iter().map(|cell| cell.glyph).collect::<String>();
+            match self {
+                Render::Plain => line.push_str(&text),
+                _ if style == Style::default() => line.push_str(&self.escape(&text)),
+                Render::Ansi => line.push_str(&format!("{}{}\x1b[0m", style.ansi(), text)),
+                Render::Html => line.push_str(&format!("<span style=\"{}\">{}</span>", style.html(), self.escape(&text))),
+            }
+        }
+
+        line
+    }
+
+    fn escape(self, text: &str) -> String {
+        match self {
+            Render::Html => text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;"),
+            _ => text.to_owned(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_line() {
+        let cells = [Cell::new('a'), Cell::new('▅').fg(Color::Red), Cell::new('▅').fg(Color::Red), Cell::new('<').bg(Color::Blue).bold()];
+
+        assert_eq!(Render::Plain.line(&cells), "a▅▅<");
+        assert_eq!(Render::Ansi.line(&cells), "a\x1b[31m▅▅\x1b[0m\x1b[1;44m<\x1b[0m");
+        assert_eq!(
+            Render::Html.line(&cells),
+            "a<span style=\"color:red\">▅▅</span><span style=\"background-color:blue;font-weight:bold\">&lt;</span>",
+        );
+    }
+}
@@ -0,0 +1,171 @@
+use serde::{Serialize, Deserialize};
+
+// directions a line leaves a cell in
+pub const UP: u8 = 1;
+pub const DOWN: u8 = 2;
+pub const LEFT: u8 = 4;
+pub const RIGHT: u8 = 8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum GlyphStyle {
+    #[default]
+    Ascii,
+    Light,
+    Heavy,
+    Double,
+    Rounded,
+}
+
+const STYLES: [GlyphStyle; 5] = [
+    GlyphStyle::Ascii,
+    GlyphStyle::Light,
+    GlyphStyle::Heavy,
+    GlyphStyle::Double,
+    GlyphStyle::Rounded,
+];
+
+impl GlyphStyle {
+    pub fn by_name(name: &str) -> Option<GlyphStyle> {
+        match name {
+            "ascii" => Some(GlyphStyle::Ascii),
+            "light" | "unicode" => Some(GlyphStyle::Light),
+            "heavy" => Some(GlyphStyle::Heavy),
+            "double" => Some(GlyphStyle::Double),
+            "rounded" => Some(GlyphStyle::Rounded),
+            _ => None,
+        }
+    }
+
+    // ─ │ ┌ ┐ └ ┘ ├ ┤ ┬ ┴ ┼
+    fn table(self) -> [char; 11] {
+        match self {
+            GlyphStyle::Ascii => ['-', '|', '+', '+', '+', '+', '+', '+', '+', '+', '+'],
+            GlyphStyle::Light => ['─', '│', '┌', '┐', '└', '┘', '├', '┤', '┬', '┴', '┼'],
+            GlyphStyle::Heavy => ['━', '┃', '┏', '┓', '┗', '┛', '┣', '┫', '┳', '┻', '╋'],
+            GlyphStyle::Double => ['═', '║', '╔', '╗', '╚', '╝', '╠', '╣', '╦', '╩', '╬'],
+            GlyphStyle::Rounded => ['─', '│', '╭', '╮', '╰', '╯', '├', '┤', '┬', '┴', '┼'],
+        }
+    }
+
+    // a line which leaves a cell only in one direction is drawn through the whole cell
+    pub fn line(self, directions: u8) -> char {
+        let table = self.table();
+        match directions & (UP | DOWN | LEFT | RIGHT) {
+            0 => ' ',
+            d if d & (LEFT | RIGHT) == 0 => table[1],
+            d if d & (UP | DOWN) == 0 => table[0],
+            d if d == DOWN | RIGHT => table[2],
+            d if d == DOWN | LEFT => table[3],
+            d if d == UP | RIGHT => table[4],
+            d if d == UP | LEFT => table[5],
+            d if d == UP | DOWN | RIGHT => table[6],
+            d if d == UP | DOWN | LEFT => table[7],
+            d if d == DOWN | LEFT | RIGHT => table[8],
+            d if d == UP | LEFT | RIGHT => table[9],
+            _ => table[10],
+        }
+    }
+
+    // two straight lines which pass through a cell without joining, in ascii the horizontal one is
+    // drawn over the vertical one, so + is left only for corners and junctions
+    pub fn crossing(self) -> char {
+        match self {
+            GlyphStyle::Ascii => self.horizontal(),
+            _ => self.line(UP | DOWN | LEFT | RIGHT),
+        }
+    }
+
+    pub fn horizontal(self) -> char {
+        self.line(LEFT | RIGHT)
+    }
+
+    pub fn vertical(self) -> char {
+        self.line(UP | DOWN)
+    }
+
+    pub fn arrow(self) -> char {
+        match self {
+            GlyphStyle::Ascii => 'v',
+            _ => '▼',
+        }
+    }
+
+    pub fn arrow_up(self) -> char {
+        match self {
+            GlyphStyle::Ascii => '^',
+            _ => '▲',
+        }
+    }
+
+    pub fn contains(self, c: char) -> bool {
+        self.table().contains(&c)
+    }
+}
+
+// a straight line is put over a straight line of the other axis
+pub fn crosses(lines: u8, line: u8) -> bool {
+    (lines == UP | DOWN && line == LEFT | RIGHT) || (lines == LEFT | RIGHT && line == UP | DOWN)
+}
+
+// style and directions of a line glyph, the first style wins for glyphs which are shared
+pub fn directions(c: char) -> Option<(GlyphStyle, u8)> {
+    match c {
+        '-' => return Some((GlyphStyle::Ascii, LEFT | RIGHT)),
+        '|' => return Some((GlyphStyle::Ascii, UP | DOWN)),
+        '+' => return Some((GlyphStyle::Ascii, UP | DOWN | LEFT | RIGHT)),
+        _ => (),
+    }
+
+    const DIRECTIONS: [u8; 11] = [
+        LEFT | RIGHT,
+        UP | DOWN,
+        DOWN | RIGHT,
+        DOWN | LEFT,
+        UP | RIGHT,
+        UP | LEFT,
+        UP | DOWN | RIGHT,
+        UP | DOWN | LEFT,
+        DOWN | LEFT | RIGHT,
+        UP | LEFT | RIGHT,
+        UP | DOWN | LEFT | RIGHT,
+    ];
+
+    STYLES[1..].iter().find_map(|&style| {
+        let position = style.table().iter().position(|&glyph| glyph == c)?;
+        Some((style, DIRECTIONS[position]))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_line_glyphs() {
+        assert_eq!(GlyphStyle::Light.line(DOWN | RIGHT), '┌');
+        assert_eq!(GlyphStyle::Heavy.line(UP | LEFT | RIGHT), '┻');
+        assert_eq!(GlyphStyle::Double.line(UP | DOWN | LEFT | RIGHT), '╬');
+        assert_eq!(GlyphStyle::Rounded.line(UP | LEFT), '╯');
+        assert_eq!(GlyphStyle::Ascii.line(UP | DOWN | LEFT), '+');
+        assert_eq!(GlyphStyle::Light.line(LEFT), '─');
+        assert_eq!(GlyphStyle::Light.line(0), ' ');
+        assert_eq!(GlyphStyle::Ascii.crossing(), '-');
+        assert_eq!(GlyphStyle::Light.crossing(), '┼');
+        assert!(crosses(UP | DOWN, LEFT | RIGHT));
+        assert!(!crosses(UP | DOWN, LEFT));
+    }
+
+    #[test]
+    fn test_directions() {
+        assert_eq!(directions('┤'), Some((GlyphStyle::Light, UP | DOWN | LEFT)));
+        assert_eq!(directions('╭'), Some((GlyphStyle::Rounded, DOWN | RIGHT)));
+        assert_eq!(directions('-'), Some((GlyphStyle::Ascii, LEFT | RIGHT)));
+        assert_eq!(directions('v'), None);
+        for style in STYLES.iter() {
+            for d in 1..16 {
+                let (_, decoded) = directions(style.line(d)).unwrap();
+                assert_eq!(style.line(decoded), style.line(d));
+            }
+        }
+    }
+}
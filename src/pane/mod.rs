@@ -1,13 +1,22 @@
+#[allow(clippy::module_inception)]
 mod pane;
+pub mod glyph;
+mod cell;
+
+pub use glyph::GlyphStyle;
+pub use cell::{Cell, Color, Render, Style};
 
 pub use pane::{
     ColumnFittablePane,
     ConnectedPane,
+    DistancePane,
     Pane,
     PaneSettings,
     StrPane,
     Point,
     ConnectorType,
+    BoxOrder,
+    Routing,
     Shape,
     Surface,
     MatrixPane,
@@ -1,27 +1,32 @@
-use std::collections::BTreeMap;
-use graph::Graph;
+use std::collections::{BTreeMap, BTreeSet};
+use graph::{algorithm::AllPairs, Graph, Weight};
+use serde::{Serialize, Deserialize};
 
+use super::cell::{Cell, Render};
+use super::glyph::{self, GlyphStyle};
+
+#[derive(Serialize, Deserialize)]
 pub struct MatrixPane {
-    gh: Graph<String>,
-    pub node_list: NodeList<String>,
-    c: String,
+    gh: Graph<Cell>,
+    pub node_list: NodeList,
+    c: Cell,
     size: (usize, usize),
 }
 
-type NodeList<T> = Vec<std::rc::Rc<std::cell::RefCell<graph::Node<T>>>>;
+type NodeList = Vec<graph::NodeId>;
 
 impl MatrixPane {
-    pub fn new(width: usize, hight: usize, c: &str) -> Self {
-        let (gh, node_list) = MatrixPane::create_matrix_graph(width, hight, String::from(c));
+    pub fn new(width: usize, hight: usize, c: Cell) -> Self {
+        let (gh, node_list) = MatrixPane::create_matrix_graph(width, hight, c);
         MatrixPane {
             size: (width, hight),
-            c: c.to_owned(),
+            c,
             gh,
             node_list,
         }
     }
 
-    pub fn create_matrix_graph<T: Clone + Eq + Ord>(w: usize, h: usize, d: T) -> (Graph<T>, NodeList<T>) {
+    pub fn create_matrix_graph<T: Clone + Eq + Ord>(w: usize, h: usize, d: T) -> (Graph<T>, NodeList) {
         let mut gh = Graph::new();
         let mut node_list = Vec::new();
         (0..w*h).for_each(|_| {node_list.push(gh.add_node(d.clone()));});
@@ -30,13 +35,13 @@ impl MatrixPane {
             if i % w == 0 {
                 continue;
             }
-            Graph::link(node_list[i-1].clone(), node_list[i].clone(), 10);
-            Graph::link(node_list[i].clone(), node_list[i-1].clone(), 10);
+            gh.link(node_list[i-1], node_list[i], 10);
+            gh.link(node_list[i], node_list[i-1], 10);
         }
 
         for i in 0..(w*h-w) {
-            Graph::link(node_list[i].clone(), node_list[i+w].clone(), 10);
-            Graph::link(node_list[i+w].clone(), node_list[i].clone(), 10);
+            gh.link(node_list[i], node_list[i+w], 10);
+            gh.link(node_list[i+w], node_list[i], 10);
         }
 
         (gh, node_list)
@@ -44,45 +49,143 @@ impl MatrixPane {
 
     pub fn orig_pane(&self) -> Pane {
         let mut lines = Vec::new();
-        for i in 0..self.size.1 {
-            let s = vec![self.c.clone(); self.size.0];
-            let line = s.join(" ");
-            lines.push(StrPane::new(&line).pane());
+        for _ in 0..self.size.1 {
+            lines.push(row_pane(&vec![self.c; self.size.0], Cell::default()));
         }
 
         ColumnFittablePane::new(lines).pane()
     }
 
     pub fn get_xy(&self, n: usize) -> (usize, usize) {
-        let (w, h) = self.size;
+        let (w, _) = self.size;
         let y = n / w;
         let x = n % w;
         (x, y)
     }
 
+    pub fn get_index(&self, x: usize, y: usize) -> Option<usize> {
+        let (w, h) = self.size;
+        if x < w && y < h {
+            Some(y * w + x)
+        } else {
+            None
+        }
+    }
+
     pub fn clean(&mut self) {
-        let c = self.c.clone();
-        self.gh.for_each(|mut n| n.data = c.clone())
+        let c = self.c;
+        self.gh.for_each(|n| n.data = c)
     }
 
-    pub fn get_node(&mut self, index: usize) -> Option<std::rc::Rc<std::cell::RefCell<graph::Node<String>>>> {
-        self.gh.node_by_index(index)
+    pub fn get_node(&mut self, index: usize) -> Option<&mut graph::Node<Cell>> {
+        self.gh.node_by_index_mut(index)
     }
 
-    pub fn graph(&self) -> &Graph<String> {
+    // sets the cost of entering the cell from any of its neighbors
+    pub fn set_terrain(&mut self, index: usize, weight: Weight) {
+        for (neighbor, _) in self.gh.neighbors(index) {
+            let edges = self.gh.node_by_index(neighbor).and_then(|node| node.edges.clone()).unwrap_or_default();
+            for edge in edges {
+                if let Some(link) = self.gh.link_by_index_mut(edge).filter(|link| link.to == index) {
+                    link.weight = weight;
+                }
+            }
+        }
+    }
+
+    // blocked cells are left blank
+    fn symbol(&self, index: usize) -> Cell {
+        match self.gh.node_by_index(index) {
+            Some(node) if !node.blocked => node.data,
+            _ => Cell::default(),
+        }
+    }
+
+    pub fn block(&mut self, index: usize) -> bool {
+        self.gh.block(index)
+    }
+
+    pub fn unblock(&mut self, index: usize) -> bool {
+        self.gh.unblock(index)
+    }
+
+    // a link which goes out of the node, in the order of creation
+    pub fn get_link(&mut self, index: usize, nth: usize) -> Option<&mut graph::Link> {
+        let edge = *self.gh.node_by_index(index)?.edges.as_ref()?.get(nth)?;
+        self.gh.link_by_index_mut(edge)
+    }
+
+    pub fn graph(&self) -> &Graph<Cell> {
         &self.gh
     }
 
-    pub fn structure(&self) -> Pane {
+    // a matrix read from a file is checked, so it can't fail once it's drawn
+    pub fn validate(&self) -> Result<(), String> {
+        let (w, h) = self.size;
+        if w.checked_mul(h) != Some(self.node_list.len()) {
+            return Err(format!("a {}x{} matrix has {} nodes", w, h, self.node_list.len()));
+        }
+        if w == 0 && !self.node_list.is_empty() {
+            return Err(String::from("a matrix with nodes has no columns"));
+        }
+        if let Some(node) = self.node_list.iter().find(|&&node| self.gh.node_by_index(node).is_none()) {
+            return Err(format!("node {} of the matrix isn't in its graph", node));
+        }
+
+        Ok(())
+    }
+
+    pub fn min_weight(&self) -> Weight {
+        let gh = &self.gh;
+        let weights = gh.indices().flat_map(|i| gh.neighbors(i)).map(|(_, weight)| weight);
+        weights.min().map_or(0, |weight| weight.max(0))
+    }
+
+    // draws the matrix with the given links between neighbor cells, cut links are crossed out
+    pub fn links_pane(&self, links: &[(usize, usize)], cut: &[(usize, usize)]) -> Pane {
+        let undirected = |links: &[(usize, usize)]| links.iter()
+            .map(|&(from, to)| (std::cmp::min(from, to), std::cmp::max(from, to)))
+            .collect::<BTreeSet<(usize, usize)>>();
+        let linked = undirected(links);
+        let cut = undirected(cut);
+        let connector = |link: (usize, usize), line: char| {
+            if cut.contains(&link) {
+                Cell::new('x')
+            } else if linked.contains(&link) {
+                Cell::new(line)
+            } else {
+                Cell::default()
+            }
+        };
+
+        let (w, h) = self.size;
         let mut lines = Vec::new();
-        for (i, node) in self.node_list.iter().enumerate() {
-            let node = node.borrow();
-            let mut weights = Vec::new();
-            if let Some(edges) = &node.edges {
-                for c in edges {
-                    weights.push(format!("{}", c.weight))
+        for (y, chunk) in self.node_list.chunks(w.max(1)).enumerate() {
+            let mut line = Vec::new();
+            for (x, node) in chunk.iter().enumerate() {
+                let i = y * w + x;
+                if x > 0 {
+                    line.push(connector((i - 1, i), '-'));
                 }
+                line.push(self.symbol(*node));
             }
+            lines.push(cells_pane(&line));
+
+            if y + 1 < h {
+                let connectors = (0..w)
+                    .map(|x| connector((y * w + x, (y + 1) * w + x), '|'))
+                    .collect::<Vec<Cell>>();
+                lines.push(row_pane(&connectors, Cell::default()));
+            }
+        }
+
+        ColumnFittablePane::new(lines).pane()
+    }
+
+    pub fn structure(&self) -> Pane {
+        let mut lines = Vec::new();
+        for (i, &node) in self.node_list.iter().enumerate() {
+            let weights = self.gh.links_from(node).map(|c| format!("{}", c.weight)).collect::<Vec<String>>();
 
             let line = format!("{} | {}", i, weights.join(" "));
             lines.push(StrPane::new(&line).pane())
@@ -94,14 +197,81 @@ impl MatrixPane {
 
 impl Surface for MatrixPane {
     fn size(&self) -> (usize, usize) {
-        return (0,0)
+        (0,0)
+    }
+
+    fn pane(&self) -> Pane {
+        let mut lines = Vec::new();
+        for chunk in self.node_list.chunks(self.size.0.max(1)) {
+            let cells = chunk.iter().map(|&n| self.symbol(n)).collect::<Vec<Cell>>();
+            lines.push(row_pane(&cells, Cell::default()));
+        }
+
+        ColumnFittablePane::new(lines).pane()
+    }
+}
+
+fn cells_pane(cells: &[Cell]) -> Pane {
+    let mut pane = Pane::new(cells.len(), 1);
+    for (x, &cell) in cells.iter().enumerate() {
+        pane.put_cell(Shape::Point(Point::new(x, 0)), cell);
+    }
+
+    pane
+}
+
+// a single row of cells with the separator between each two of them
+fn row_pane(cells: &[Cell], separator: Cell) -> Pane {
+    let mut row = Vec::new();
+    for (i, &cell) in cells.iter().enumerate() {
+        if i > 0 {
+            row.push(separator);
+        }
+        row.push(cell);
+    }
+
+    cells_pane(&row)
+}
+
+pub struct DistancePane<'a> {
+    table: &'a AllPairs,
+}
+
+impl<'a> DistancePane<'a> {
+    pub fn new(table: &'a AllPairs) -> Self {
+        DistancePane {
+            table,
+        }
+    }
+
+    fn rows(&self) -> Vec<Vec<String>> {
+        let mut header = vec![String::new()];
+        header.extend(self.table.nodes.iter().map(|n| n.to_string()));
+
+        let mut rows = vec![header];
+        for (node, distances) in self.table.nodes.iter().zip(&self.table.dist) {
+            let mut row = vec![node.to_string()];
+            row.extend(distances.iter().map(|d| d.map_or(String::from("-"), |d| d.to_string())));
+            rows.push(row);
+        }
+
+        rows
+    }
+}
+
+impl<'a> Surface for DistancePane<'a> {
+    fn size(&self) -> (usize, usize) {
+        self.pane().size()
     }
 
     fn pane(&self) -> Pane {
+        let rows = self.rows();
+        let cell_size = rows.iter().flatten().map(|cell| cell.chars().count()).max().unwrap_or(0);
+
         let mut lines = Vec::new();
-        for chunk in self.node_list.chunks(self.size.0) {
-            let s = chunk.iter().map(|n| n.borrow().data.clone()).collect::<Vec<String>>();
-            let line = s.join(" ");
+        for row in rows {
+            let cells = row[1..].iter().map(|cell| format!("{: >1$}", cell, cell_size)).collect::<Vec<String>>();
+            let line = format!("{: >2$} | {}", row[0], cells.join(" "), cell_size);
             lines.push(StrPane::new(&line).pane());
         }
 
@@ -135,13 +305,10 @@ impl Surface for ColumnFittablePane {
         let size = self.size();
         let mut pane = Pane::new(size.0, size.1);
 
-        let mut i = 0;
+        let mut row = 0;
         for p in &self.panes {
-            let str_pane = p.to_string();
-            for line in str_pane.lines() {
-                StrPane::str_pane(&mut pane, line, i);
-                i += 1;
-            }
+            pane.draw(p, Point::new(0, row));
+            row += p.size().1;
         }
 
         pane
@@ -183,24 +350,82 @@ impl<'a> Surface for StrPane<'a> {
 }
 
 pub struct ConnectedPane {
-    connected_list: Vec<(usize, usize)>,
+    connected_list: Vec<(usize, usize, Option<String>)>,
     concept: Vec<usize>,
     settings: PaneSettings,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PaneSettings {
     pub gap_size: usize,
     pub connection_size: usize,
     pub connection_type: ConnectorType,
+    pub box_order: BoxOrder,
+    #[serde(default)]
+    pub glyphs: GlyphStyle,
+    #[serde(default)]
+    pub routing: Routing,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+// which side of boxes connections are drawn on
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Routing {
+    #[default]
+    Above,
+    Below,
+    // splits connections between both sides to keep the picture low
+    Auto,
+}
+
+impl Routing {
+    pub fn by_name(name: &str) -> Option<Routing> {
+        match name {
+            "above" => Some(Routing::Above),
+            "below" => Some(Routing::Below),
+            "auto" => Some(Routing::Auto),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BoxOrder {
+    Insertion,
+    // connections go from left to right wherever cycles allow it
+    Topological,
+    // boxes are moved so connections cross less and stay short
+    Crossings,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ConnectorType {
     General,
     Arrow,
 }
 
+// a connection placed on its side of the boxes
+#[derive(Debug, Clone, Copy)]
+struct Route {
+    lhs: usize,
+    rhs: usize,
+    side: Side,
+    // levels are counted from the one farthest from boxes
+    level: usize,
+    label: Option<usize>,
+}
+
+impl Route {
+    fn span(&self) -> (usize, usize) {
+        (self.lhs.min(self.rhs), self.lhs.max(self.rhs))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Side {
+    Above,
+    Below,
+}
+
 impl ConnectedPane {
     pub fn new(concept: &[usize], settings: PaneSettings) -> Self {
         ConnectedPane {
@@ -211,80 +436,223 @@ impl ConnectedPane {
     }
 
     pub fn connect(&mut self, who: usize, to: usize) {
-        self.connected_list.push((who, to));
+        self.connect_with(who, to, None);
+    }
+
+    // the label is written on the horizontal line of the connection
+    pub fn connect_with(&mut self, who: usize, to: usize, label: Option<String>) {
+        let label = label.filter(|label| !label.is_empty());
+        self.connected_list.push((who, to, label));
         self.connected_list.sort();
     }
 
-    fn start_element_index(&self, i: usize) -> usize {
-        self.concept.iter().take(i).sum::<usize>() + i * self.settings.gap_size
+    fn start_element_index(&self, i: usize, gap_size: usize) -> usize {
+        self.concept.iter().take(i).sum::<usize>() + i * gap_size
+    }
+
+    // The gap of the settings is widened until labels fit between lines crossing their levels.
+    // When some never fit, the gap which fits the most of them is taken.
+    pub fn gap_size(&self) -> usize {
+        let labels = self.connected_list.iter().filter(|(_, _, label)| label.is_some()).count();
+        let longest = self.connected_list.iter().filter_map(|(_, _, label)| label.as_ref()).map(|label| label.chars().count()).max();
+        let gaps = match longest {
+            Some(longest) => self.settings.gap_size..=self.settings.gap_size + longest + 2,
+            None => return self.settings.gap_size,
+        };
+
+        let placed = |gap: usize| self.routes(gap).iter().filter(|route| route.label.is_some()).count();
+        gaps.clone()
+            .find(|&gap| placed(gap) == labels)
+            .or_else(|| gaps.max_by_key(|&gap| (placed(gap), std::cmp::Reverse(gap))))
+            .unwrap_or(self.settings.gap_size)
     }
 
-    fn connector(ct: ConnectorType) -> char {
-        match ct {
-            ConnectorType::General => '|',
-            ConnectorType::Arrow => 'v',
+    fn routes(&self, gap_size: usize) -> Vec<Route> {
+        let sides = self.sides();
+        let columns = self.columns(gap_size, &sides);
+        let mut routes = columns.into_iter().zip(sides).map(|((lhs, rhs), side)| Route { lhs, rhs, side, level: 0, label: None }).collect::<Vec<Route>>();
+        for side in [Side::Above, Side::Below] {
+            let indices = (0..routes.len()).filter(|&i| routes[i].side == side).collect::<Vec<usize>>();
+            let spans = indices.iter().map(|&i| routes[i].span()).collect::<Vec<_>>();
+            for (&i, level) in indices.iter().zip(pack(&spans)) {
+                routes[i].level = level;
+            }
         }
+        self.place_labels(&mut routes);
+
+        routes
     }
-}
 
-impl Surface for ConnectedPane {
-    fn size(&self) -> (usize, usize) {
-        let width =
-            self.concept.iter().sum::<usize>() + (self.concept.len() - 1) * self.settings.gap_size;
-        let hight = self.connected_list.len() * 2;
+    // Which side every connection goes on. In the auto mode levels of connections drawn above
+    // take turns going below, so both sides keep long lines far from boxes and short near them.
+    fn sides(&self) -> Vec<Side> {
+        let count = self.connected_list.len();
+        let above = vec![Side::Above; count];
+        match self.settings.routing {
+            Routing::Above => above,
+            Routing::Below => vec![Side::Below; count],
+            Routing::Auto => {
+                let spans = self.spans(&above, Side::Above);
+                let split = pack(&spans).into_iter()
+                    .map(|level| if level % 2 == 0 { Side::Above } else { Side::Below })
+                    .collect::<Vec<Side>>();
+
+                // lines of a side take other columns than the split assumed, it's dropped when it's higher
+                if self.levels(&split) <= self.levels(&above) {
+                    split
+                } else {
+                    above
+                }
+            },
+        }
+    }
 
-        (width, hight)
+    fn spans(&self, sides: &[Side], side: Side) -> Vec<(usize, usize)> {
+        self.columns(self.settings.gap_size, sides).into_iter().zip(sides)
+            .filter(|(_, &other)| other == side)
+            .map(|((lhs, rhs), _)| (lhs.min(rhs), lhs.max(rhs)))
+            .collect()
     }
 
-    fn pane(&self) -> Pane {
-        let (width, hight) = self.size();
-        let mut pane = Pane::new(width, hight);
-        struct LineCoordinate {
-            from: Point,
-            to: Point,
-            lhs_connection: Point,
-            rhs_connection: Point,
-        };
+    // levels taken on both sides together
+    fn levels(&self, sides: &[Side]) -> usize {
+        [Side::Above, Side::Below].iter()
+            .map(|&side| pack(&self.spans(sides, side)).into_iter().max().map_or(0, |level| level + 1))
+            .sum()
+    }
 
-        let mut coordinates: Vec<LineCoordinate> = Vec::new();
-        let mut used: BTreeMap<usize, usize> = BTreeMap::new();
-        let mut current_level = 0;
-        for (from, to) in &self.connected_list {
-            let from_index = self.start_element_index(*from);
-            let to_index = self.start_element_index(*to);
+    // columns of lines going from and to boxes, every side takes columns of a box from its left edge
+    fn columns(&self, gap_size: usize, sides: &[Side]) -> Vec<(usize, usize)> {
+        let mut used: BTreeMap<(Side, usize), usize> = BTreeMap::new();
+        let mut offset = |side: Side, i: usize| *used.entry((side, i)).and_modify(|e| *e += self.settings.connection_size).or_default();
+        self.connected_list.iter().zip(sides).map(|((from, to, _), &side)| {
+            let from_diff = offset(side, *from);
+            let to_diff = offset(side, *to);
 
-            let from_diff = *used.entry(*from).and_modify(|e| *e += self.settings.connection_size).or_default();
-            let to_diff = *used.entry(*to).and_modify(|e| *e += self.settings.connection_size).or_default();
+            (self.start_element_index(*from, gap_size) + from_diff, self.start_element_index(*to, gap_size) + to_diff)
+        }).collect()
+    }
 
-            let lhs_connection = Point::new(from_index + from_diff, current_level + 1);
-            let rhs_connection = Point::new(to_index + to_diff, current_level + 1);
-            let (from, to) = if lhs_connection.x > rhs_connection.x {
-                (Point::new(lhs_connection.x, current_level), Point::new(rhs_connection.x + 1, current_level))
-            } else {
-                (Point::new(lhs_connection.x + 1, current_level), Point::new(rhs_connection.x, current_level))
+    // where labels start, as close to the middle of their lines as lines of the farther levels allow
+    fn place_labels(&self, routes: &mut [Route]) {
+        for i in 0..routes.len() {
+            let label = match &self.connected_list[i].2 {
+                Some(label) => label,
+                None => continue,
             };
+            let route = routes[i];
+            let crossing = routes.iter()
+                .filter(|other| other.side == route.side && other.level < route.level)
+                .flat_map(|other| [other.lhs, other.rhs])
+                .collect::<BTreeSet<usize>>();
+
+            let length = label.chars().count();
+            let (min, max) = route.span();
+            let middle = (min + max) / 2;
+
+            // a line is left on both sides of a label
+            let mut starts = (min + 2..).take_while(|start| start + length < max).collect::<Vec<usize>>();
+            starts.sort_by_key(|start| (start + length / 2).abs_diff(middle));
+            routes[i].label = starts.into_iter().find(|start| crossing.range(start - 1..=start + length).next().is_none());
+        }
+    }
 
-            coordinates.push(LineCoordinate {from, to, lhs_connection, rhs_connection});
-
-            current_level += 2;
+    fn connector(&self, side: Side) -> char {
+        match (self.settings.connection_type, side) {
+            (ConnectorType::General, _) => self.settings.glyphs.vertical(),
+            (ConnectorType::Arrow, Side::Above) => self.settings.glyphs.arrow(),
+            (ConnectorType::Arrow, Side::Below) => self.settings.glyphs.arrow_up(),
         }
+    }
+
+    fn width(&self, gap_size: usize) -> usize {
+        self.concept.iter().sum::<usize>() + self.concept.len().saturating_sub(1) * gap_size
+    }
+
+    // connections routed under boxes, the pane of the Surface holds the ones above them
+    pub fn below_pane(&self) -> Pane {
+        self.side_pane(Side::Below)
+    }
 
-        let connector = ConnectedPane::connector(self.settings.connection_type);
-        for coordinate in coordinates {
-            let lhs_conn_down = Point::new(coordinate.lhs_connection.x, hight);
-            let rhs_conn_down = Point::new(coordinate.rhs_connection.x, hight);
-            pane.put(Shape::Line(coordinate.lhs_connection, lhs_conn_down), '|');
-            pane.put(Shape::Line(coordinate.rhs_connection, rhs_conn_down), '|');
+    fn side_pane(&self, side: Side) -> Pane {
+        let gap_size = self.gap_size();
+        let routes = self.routes(gap_size).into_iter().zip(&self.connected_list)
+            .filter(|(route, _)| route.side == side)
+            .collect::<Vec<_>>();
+        let hight = routes.iter().map(|(route, _)| route.level * 2 + 2).max().unwrap_or(0);
+        let glyphs = self.settings.glyphs;
+        let mut pane = Pane::with_glyphs(self.width(gap_size), hight, glyphs);
+
+        for (route, (_, _, label)) in routes {
+            // lines join into corners on the level, and cross lines of the farther levels,
+            // below boxes the picture is mirrored
+            let (level, from, to, end) = match side {
+                Side::Above => (route.level * 2, route.level * 2, hight, hight - 1),
+                Side::Below => (hight - 1 - route.level * 2, 0, hight - route.level * 2, 0),
+            };
+            pane.put(Shape::Line(Point::new(route.lhs, from), Point::new(route.lhs, to)), glyphs.vertical());
+            pane.put(Shape::Line(Point::new(route.rhs, from), Point::new(route.rhs, to)), glyphs.vertical());
+            let (min, max) = route.span();
+            pane.put(Shape::Line(Point::new(min, level), Point::new(max + 1, level)), glyphs.horizontal());
 
-            pane.put(Shape::Point(Point::new(rhs_conn_down.x, rhs_conn_down.y - 1)), connector);
+            pane.put(Shape::Point(Point::new(route.rhs, end)), self.connector(side));
 
-            pane.put(Shape::Line(coordinate.from, coordinate.to), '-');
+            if let (Some(label), Some(start)) = (label, route.label) {
+                pane.write(Point::new(start, level), label);
+            }
         }
 
         pane
     }
 }
 
+// Puts spans on as few levels as possible, a level holds spans which don't share columns.
+// Levels are ordered by their longest span, so long lines go farther from boxes.
+fn pack(spans: &[(usize, usize)]) -> Vec<usize> {
+    let mut order = (0..spans.len()).collect::<Vec<usize>>();
+    order.sort_by_key(|&i| spans[i]);
+
+    let mut ends: Vec<usize> = Vec::new();
+    let mut levels = vec![0; spans.len()];
+    for i in order {
+        let (min, max) = spans[i];
+        match ends.iter().position(|&end| end + 1 < min) {
+            Some(level) => {
+                ends[level] = max;
+                levels[i] = level;
+            },
+            None => {
+                levels[i] = ends.len();
+                ends.push(max);
+            },
+        }
+    }
+
+    let mut longest = vec![0; ends.len()];
+    for (i, &level) in levels.iter().enumerate() {
+        longest[level] = longest[level].max(spans[i].1 - spans[i].0);
+    }
+    let mut by_length = (0..ends.len()).collect::<Vec<usize>>();
+    by_length.sort_by_key(|&level| std::cmp::Reverse(longest[level]));
+    let mut renamed = vec![0; ends.len()];
+    for (new, &old) in by_length.iter().enumerate() {
+        renamed[old] = new;
+    }
+
+    levels.into_iter().map(|level| renamed[level]).collect()
+}
+
+impl Surface for ConnectedPane {
+    fn size(&self) -> (usize, usize) {
+        let pane = self.side_pane(Side::Above);
+        pane.size()
+    }
+
+    fn pane(&self) -> Pane {
+        self.side_pane(Side::Above)
+    }
+}
+
 pub trait Surface {
     fn size(&self) -> (usize, usize);
     fn pane(&self) -> Pane;
@@ -293,14 +661,23 @@ pub trait Surface {
 #[derive(Debug)]
 pub struct Pane {
     size: (usize, usize),
-    surface: Vec<Vec<char>>,
+    surface: Vec<Vec<Cell>>,
+    // directions lines leave every cell in, so lines put over each other are joined
+    lines: Vec<Vec<u8>>,
+    glyphs: GlyphStyle,
 }
 
 impl Pane {
     pub fn new(width: usize, hight: usize) -> Self {
+        Pane::with_glyphs(width, hight, GlyphStyle::default())
+    }
+
+    pub fn with_glyphs(width: usize, hight: usize, glyphs: GlyphStyle) -> Self {
         Pane {
             size: (width, hight),
-            surface: vec![vec![' '; width]; hight],
+            surface: vec![vec![Cell::default(); width]; hight],
+            lines: vec![vec![0; width]; hight],
+            glyphs,
         }
     }
 
@@ -309,43 +686,99 @@ impl Pane {
     }
 
     pub fn put(&mut self, shape: Shape, c: char) {
+        self.put_cell(shape, Cell::new(c));
+    }
+
+    // Line glyphs are merged with the ones already in the pane into junctions, ends of a line
+    // don't go beyond it. Anything else overwrites the cell.
+    pub fn put_cell(&mut self, shape: Shape, c: Cell) {
         match shape {
             Shape::Point(Point { x, y }) => {
-                self.surface[y][x] = c;
+                self.put_glyph(x, y, c, glyph::UP | glyph::DOWN | glyph::LEFT | glyph::RIGHT);
             }
             Shape::Line(point1, point2) => {
-                //TODO: simplify
                 if point1.y == point2.y {
-                    let mut min = std::cmp::min(point1.x, point2.x);
+                    let min = std::cmp::min(point1.x, point2.x);
                     let max = std::cmp::max(point1.x, point2.x);
-
-                    while min < max {
-                        self.surface[point1.y][min] = c;
-                        min += 1;
+                    for x in min..max {
+                        let first = if x == min { glyph::LEFT } else { 0 };
+                        let last = if x + 1 == max { glyph::RIGHT } else { 0 };
+                        self.put_glyph(x, point1.y, c, !(first | last));
                     }
                 } else if point1.x == point2.x {
-                    let mut min = std::cmp::min(point1.y, point2.y);
+                    let min = std::cmp::min(point1.y, point2.y);
                     let max = std::cmp::max(point1.y, point2.y);
-
-                    while min < max {
-                        self.surface[min][point1.x] = c;
-                        min += 1;
+                    for y in min..max {
+                        let first = if y == min { glyph::UP } else { 0 };
+                        let last = if y + 1 == max { glyph::DOWN } else { 0 };
+                        self.put_glyph(point1.x, y, c, !(first | last));
                     }
                 }
             }
         }
     }
-}
 
-impl std::fmt::Display for Pane {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn put_glyph(&mut self, x: usize, y: usize, c: Cell, directions: u8) {
+        match glyph::directions(c.glyph) {
+            Some((style, line)) => {
+                // a glyph shared by several styles is drawn in the pane's one
+                let style = if self.glyphs.contains(c.glyph) { self.glyphs } else { style };
+                let (before, line) = (self.lines[y][x], line & directions);
+                self.lines[y][x] |= line;
+                let glyph = match glyph::crosses(before, line) {
+                    true => style.crossing(),
+                    false => style.line(self.lines[y][x]),
+                };
+                self.surface[y][x] = Cell { glyph, ..c };
+            },
+            None => {
+                self.lines[y][x] = 0;
+                self.surface[y][x] = c;
+            },
+        }
+    }
+
+    // text is written as it is, without joining it with lines
+    pub fn write(&mut self, at: Point, text: &str) {
+        for (x, c) in text.chars().enumerate() {
+            if let Some(cell) = self.surface.get_mut(at.y).and_then(|row| row.get_mut(at.x + x)) {
+                *cell = Cell::new(c);
+                self.lines[at.y][at.x + x] = 0;
+            }
+        }
+    }
+
+    // copies the other pane into this one with its top left corner at the point,
+    // the part which doesn't fit is cut
+    pub fn draw(&mut self, other: &Pane, at: Point) {
+        for (y, (cells, lines)) in other.surface.iter().zip(&other.lines).enumerate() {
+            for (x, (&cell, &line)) in cells.iter().zip(lines).enumerate() {
+                if let Some(target) = self.surface.get_mut(at.y + y).and_then(|row| row.get_mut(at.x + x)) {
+                    *target = cell;
+                    self.lines[at.y + y][at.x + x] = line;
+                }
+            }
+        }
+    }
+
+    pub fn render(&self, render: Render) -> String {
         let lines = self
             .surface
             .iter()
-            .map(|line| line.iter().collect::<String>())
+            .map(|line| render.line(line))
             .collect::<Vec<String>>()
             .join("\n");
-        write!(f, "{}", lines)
+
+        match render {
+            Render::Html => format!("<pre>{}</pre>", lines),
+            _ => lines,
+        }
+    }
+}
+
+impl std::fmt::Display for Pane {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.render(Render::Ansi))
     }
 }
 
@@ -366,3 +799,134 @@ impl Point {
         Point { x, y }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_put_joins_lines() {
+        let mut pane = Pane::with_glyphs(5, 3, GlyphStyle::Light);
+        pane.put(Shape::Line(Point::new(0, 1), Point::new(5, 1)), '─');
+        pane.put(Shape::Line(Point::new(0, 0), Point::new(0, 3)), '│');
+        pane.put(Shape::Line(Point::new(2, 0), Point::new(2, 3)), '│');
+        pane.put(Shape::Line(Point::new(4, 1), Point::new(4, 3)), '│');
+        pane.put(Shape::Line(Point::new(3, 0), Point::new(3, 2)), '│');
+
+        assert_eq!(pane.to_string(), "│ ││ \n├─┼┴┐\n│ │ │");
+    }
+
+    #[test]
+    fn test_ascii_lines_cross_without_junctions() {
+        let mut pane = Pane::with_glyphs(5, 3, GlyphStyle::Ascii);
+        pane.put(Shape::Line(Point::new(0, 1), Point::new(5, 1)), '-');
+        pane.put(Shape::Line(Point::new(0, 0), Point::new(0, 3)), '|');
+        pane.put(Shape::Line(Point::new(2, 0), Point::new(2, 3)), '|');
+        pane.put(Shape::Line(Point::new(4, 1), Point::new(4, 3)), '|');
+        pane.put(Shape::Line(Point::new(3, 0), Point::new(3, 2)), '|');
+
+        assert_eq!(pane.to_string(), "| || \n+--++\n| | |");
+    }
+
+    fn settings(gap_size: usize) -> PaneSettings {
+        PaneSettings {
+            gap_size,
+            connection_size: 1,
+            connection_type: ConnectorType::General,
+            box_order: BoxOrder::Insertion,
+            glyphs: GlyphStyle::Ascii,
+            routing: Routing::Above,
+        }
+    }
+
+    #[test]
+    fn test_labels_avoid_crossing_lines() {
+        let mut pane = ConnectedPane::new(&[5, 5, 5], settings(1));
+        pane.connect(0, 1);
+        pane.connect_with(0, 2, Some(String::from("uses")));
+
+        // the longer connection is drawn farther from boxes, so its label crosses no line
+        assert_eq!(pane.gap_size(), 1);
+        assert_eq!(pane.pane().to_string(), concat!(
+            " +--uses----+    \n",
+            " |          |    \n",
+            "+-----+     |    \n",
+            "||    |     |    ",
+        ));
+    }
+
+    #[test]
+    fn test_labels_avoid_crossing_lines_of_packed_levels() {
+        let mut pane = ConnectedPane::new(&[5, 5, 5], settings(1));
+        pane.connect(0, 2);
+        pane.connect_with(1, 2, Some(String::from("uses")));
+
+        assert_eq!(pane.gap_size(), 2);
+        assert_eq!(pane.pane().to_string(), concat!(
+            "+-------------+    \n",
+            "|             |    \n",
+            "|      +-uses--+   \n",
+            "|      |      ||   ",
+        ));
+    }
+
+    #[test]
+    fn test_labels_widen_gap() {
+        let mut pane = ConnectedPane::new(&[5, 5], settings(1));
+        pane.connect_with(0, 1, Some(String::from("depends on")));
+
+        assert_eq!(pane.gap_size(), 8);
+        assert_eq!(pane.pane().to_string(), "+-depends on-+    \n|            |    ");
+    }
+
+    #[test]
+    fn test_pack_levels() {
+        assert_eq!(pack(&[(0, 4), (6, 8), (2, 3), (5, 10)]), vec![1, 1, 0, 0]);
+        assert_eq!(pack(&[(0, 2), (3, 5)]), vec![0, 1]);
+        assert_eq!(pack(&[]), Vec::<usize>::new());
+    }
+
+    #[test]
+    fn test_connections_share_levels() {
+        let mut pane = ConnectedPane::new(&[3, 3, 3, 3], settings(1));
+        pane.connect(0, 1);
+        pane.connect(2, 3);
+
+        assert_eq!(pane.pane().to_string(), "+---+   +---+  \n|   |   |   |  ");
+    }
+
+    #[test]
+    fn test_below_pane() {
+        let mut pane = ConnectedPane::new(&[3, 3], PaneSettings { routing: Routing::Below, connection_type: ConnectorType::Arrow, ..settings(1) });
+        pane.connect(0, 1);
+
+        assert_eq!(pane.pane().size(), (7, 0));
+        assert_eq!(pane.below_pane().to_string(), "|   ^  \n+---+  ");
+    }
+
+    #[test]
+    fn test_auto_routing() {
+        let mut pane = ConnectedPane::new(&[3, 3, 3], PaneSettings { routing: Routing::Auto, ..settings(1) });
+        pane.connect(0, 2);
+        pane.connect(0, 1);
+        pane.connect(1, 2);
+
+        assert_eq!(pane.pane().to_string(), concat!(
+            "+-------+  \n",
+            "|       |  \n",
+            "|   +----+ \n",
+            "|   |   || ",
+        ));
+        assert_eq!(pane.below_pane().to_string(), "|   |      \n+---+      ");
+    }
+
+    #[test]
+    fn test_put_overwrites_text() {
+        let mut pane = Pane::with_glyphs(3, 1, GlyphStyle::Rounded);
+        pane.put(Shape::Line(Point::new(0, 0), Point::new(3, 0)), '─');
+        pane.put(Shape::Point(Point::new(1, 0)), 'x');
+        pane.put(Shape::Point(Point::new(2, 0)), '│');
+
+        assert_eq!(pane.to_string(), "─x┤");
+    }
+}
@@ -1,33 +1,176 @@
-use crate::pane::{MatrixPane};
-use graph::algorithm::{self};
+use std::collections::BTreeSet;
 
-#[derive(Debug)]
+use crate::heuristic::Scaled;
+use crate::jump_point;
+use crate::pane::{Cell, MatrixPane};
+use graph::{
+    algorithm::{self, Flow, SearchError, SearchResult, ShortestPaths},
+    Weight,
+};
+
+#[derive(Debug, Clone, Copy)]
 pub enum PathFunc {
     Dijkstra,
     AStar,
+    BellmanFord,
+    JumpPoint,
+    Bidirectional,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum TreeFunc {
+    Prim,
+    Kruskal,
+}
+
+#[derive(Debug)]
+pub struct PathReport {
+    pub result: SearchResult,
+    // None if the search doesn't use the heuristic
+    pub admissible: Option<bool>,
 }
 
-pub fn construct_path(mut matrix: MatrixPane, from: usize, look: usize, path_symbol: &str, checked_symbol: &str, func: PathFunc) -> MatrixPane {
-    let path = match func {
+pub fn search(matrix: &MatrixPane, from: usize, look: usize, func: PathFunc, heuristic: &Scaled) -> (Vec<Vec<usize>>, Result<SearchResult, SearchError>) {
+    let h = |n: usize, to: usize| heuristic.weight(matrix.get_xy(n), matrix.get_xy(to));
+
+    match func {
         PathFunc::Dijkstra => {
-            algorithm::dijkstra(matrix.graph(), from, look)
+            algorithm::dijkstra_extra(matrix.graph(), from, look)
         },
         PathFunc::AStar => {
-            let (dst_x, dst_y) = matrix.get_xy(look);
-            algorithm::a_star(matrix.graph(), from, look, |n| {
-                let (x, y) = matrix.get_xy(n);
-                f64::abs(dst_x as f64 - x  as f64) as usize + f64::abs(dst_y as f64 - y  as f64) as usize
-            })
+            algorithm::a_star_extra(matrix.graph(), from, look, |n| h(n, look))
+        },
+        PathFunc::BellmanFord => {
+            (Vec::new(), algorithm::bellman_ford(matrix.graph(), from, look))
+        },
+        PathFunc::JumpPoint => {
+            jump_point::jump_point_search(matrix, from, look)
         },
+        PathFunc::Bidirectional => {
+            algorithm::bidirectional_a_star_extra(matrix.graph(), from, look, |n| h(n, look), |n| h(n, from))
+        },
+    }
+}
+
+pub fn construct_path(matrix: &mut MatrixPane, from: usize, look: usize, path_symbol: Cell, checked_symbol: Cell, func: PathFunc, heuristic: &Scaled) -> Result<PathReport, SearchError> {
+    let (_, result) = search(matrix, from, look, func, heuristic);
+    let result = result?;
+
+    let admissible = match func {
+        PathFunc::AStar => Some(is_admissible(matrix, heuristic, look, &result.visited)),
+        PathFunc::Bidirectional => Some(
+            is_admissible(matrix, heuristic, look, &result.visited) && is_admissible_backward(matrix, heuristic, from, &result.visited)
+        ),
+        _ => None,
+    };
+
+    for p in &result.visited {
+        matrix.get_node(*p).unwrap().data = checked_symbol;
+    }
+
+    for point in &result.path {
+        matrix.get_node(*point).unwrap().data = path_symbol;
+    }
+
+    Ok(PathReport { result, admissible })
+}
+
+// marks nodes of a minimum spanning tree, returns the tree links and their total weight
+pub fn construct_spanning_tree(matrix: &mut MatrixPane, tree_symbol: Cell, func: TreeFunc) -> (Vec<(usize, usize)>, Weight) {
+    let tree = match func {
+        TreeFunc::Prim => algorithm::prim(matrix.graph()),
+        TreeFunc::Kruskal => algorithm::kruskal(matrix.graph()),
     };
+    let links = tree.pairs();
+
+    for &(from, to) in &links {
+        matrix.get_node(from).unwrap().data = tree_symbol;
+        matrix.get_node(to).unwrap().data = tree_symbol;
+    }
 
-    for p in path.as_ref().unwrap() {
-        matrix.get_node(*p.0).unwrap().borrow_mut().data = checked_symbol.to_owned();
+    (links, tree.weight)
+}
+
+// marks each of k shortest paths by its own symbol, better paths are drawn over worse ones
+pub fn construct_paths(matrix: &mut MatrixPane, from: usize, look: usize, k: usize, symbols: &[Cell]) -> Result<Vec<SearchResult>, SearchError> {
+    let results = algorithm::k_shortest_paths(matrix.graph(), from, look, k)?;
+
+    let marks: Vec<_> = results.iter().zip(symbols.iter().cycle()).collect();
+    for (result, symbol) in marks.into_iter().rev() {
+        for point in &result.path {
+            matrix.get_node(*point).unwrap().data = *symbol;
+        }
     }
 
-    for point in &algorithm::path(&path.unwrap(), look, from).unwrap() {
-        matrix.get_node(*point).unwrap().borrow_mut().data = path_symbol.to_owned();
+    Ok(results)
+}
+
+// marks nodes which stay on the source side of a minimum cut
+pub fn construct_flow(matrix: &mut MatrixPane, source: usize, sink: usize, source_side_symbol: Cell, sink_symbol: Cell) -> Result<Flow, SearchError> {
+    let flow = algorithm::max_flow(matrix.graph(), source, sink)?;
+
+    for node in &flow.source_side {
+        matrix.get_node(*node).unwrap().data = source_side_symbol;
     }
-    
-    matrix
-}
\ No newline at end of file
+    matrix.get_node(sink).unwrap().data = sink_symbol;
+
+    Ok(flow)
+}
+
+// marks each component by its own symbol, symbols are reused if there are more components
+pub fn color_components(matrix: &mut MatrixPane, components: &[Vec<usize>], symbols: &[Cell]) {
+    for (component, symbol) in components.iter().zip(symbols.iter().cycle()) {
+        for &node in component {
+            matrix.get_node(node).unwrap().data = *symbol;
+        }
+    }
+}
+
+// checks that the heuristic never overestimated a real distance to the goal on the given nodes
+pub fn is_admissible(matrix: &MatrixPane, heuristic: &Scaled, look: usize, nodes: &BTreeSet<usize>) -> bool {
+    let distances = algorithm::shortest_paths_to(matrix.graph(), look);
+    never_overestimates(matrix, heuristic, look, distances, nodes)
+}
+
+// the same for the backward search of a bidirectional one, it estimates distances from the start
+pub fn is_admissible_backward(matrix: &MatrixPane, heuristic: &Scaled, from: usize, nodes: &BTreeSet<usize>) -> bool {
+    let (_, distances) = algorithm::shortest_paths(matrix.graph(), from, None);
+    never_overestimates(matrix, heuristic, from, distances, nodes)
+}
+
+fn never_overestimates(matrix: &MatrixPane, heuristic: &Scaled, to: usize, distances: Result<ShortestPaths, SearchError>, nodes: &BTreeSet<usize>) -> bool {
+    let distances = match distances {
+        Ok(paths) => paths.dist,
+        Err(_) => return false,
+    };
+
+    nodes.iter().all(|n| match distances.get(n) {
+        Some(&distance) => heuristic.weight(matrix.get_xy(*n), matrix.get_xy(to)) <= distance,
+        None => true,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::heuristic::Manhattan;
+
+    #[test]
+    fn test_backward_heuristic_is_checked() {
+        // 0 -> 1 is cheap and 1 -> 2 is heavy, so estimates from the start are too big near it
+        let mut matrix = MatrixPane::new(3, 1, Cell::new('.'));
+        for (from, to, weight) in [(0, 1, 1), (1, 2, 10)] {
+            let nth = (0..).find(|&nth| matrix.get_link(from, nth).is_some_and(|link| link.to == to)).unwrap();
+            matrix.get_link(from, nth).unwrap().weight = weight;
+        }
+        let heuristic = Scaled::new(Box::new(Manhattan), 5);
+
+        let report = construct_path(&mut matrix, 0, 2, Cell::new('*'), Cell::new('o'), PathFunc::AStar, &heuristic).unwrap();
+        assert_eq!(report.admissible, Some(true));
+
+        matrix.clean();
+        let report = construct_path(&mut matrix, 0, 2, Cell::new('*'), Cell::new('o'), PathFunc::Bidirectional, &heuristic).unwrap();
+        assert_eq!(report.result.cost, 11);
+        assert_eq!(report.admissible, Some(false));
+    }
+}
@@ -0,0 +1,86 @@
+use std::io::{self, Read, Write};
+
+use serde::{Serialize, Deserialize};
+
+use crate::{line_gh::LineGH, pane::MatrixPane};
+
+// a file keeps both views of a session, so the diagram and the matrix are restored together
+#[derive(Serialize)]
+struct Snapshot<'a> {
+    diagram: &'a LineGH,
+    matrix: &'a MatrixPane,
+}
+
+#[derive(Deserialize)]
+struct Session {
+    diagram: LineGH,
+    matrix: MatrixPane,
+}
+
+pub fn save<W: Write>(w: W, diagram: &LineGH, matrix: &MatrixPane) -> io::Result<()> {
+    serde_json::to_writer_pretty(w, &Snapshot { diagram, matrix })?;
+    Ok(())
+}
+
+pub fn load<R: Read>(r: R) -> io::Result<(LineGH, MatrixPane)> {
+    let session: Session = serde_json::from_reader(r)?;
+    session.matrix.validate().map_err(|message| io::Error::new(io::ErrorKind::InvalidData, message))?;
+    Ok((session.diagram, session.matrix))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pane::{BoxOrder, Cell, Surface};
+
+    #[test]
+    fn test_save_and_load() {
+        let mut diagram = LineGH::new();
+        diagram.add_edge("hello");
+        diagram.add_edge("world");
+        diagram.connect(0, 1);
+        diagram.pane_settings.box_order = BoxOrder::Topological;
+
+        let mut matrix = MatrixPane::new(3, 2, Cell::new('.'));
+        matrix.block(4);
+        matrix.get_link(0, 0).unwrap().weight = 3;
+
+        let mut file = Vec::new();
+        save(&mut file, &diagram, &matrix).unwrap();
+        let (restored_diagram, restored_matrix) = load(file.as_slice()).unwrap();
+
+        assert_eq!(restored_diagram.to_string(), diagram.to_string());
+        assert_eq!(restored_diagram.pane_settings.box_order, BoxOrder::Topological);
+        assert_eq!(restored_matrix.pane().to_string(), matrix.pane().to_string());
+        assert_eq!(restored_matrix.structure().to_string(), matrix.structure().to_string());
+        assert!(restored_matrix.graph().is_blocked(4));
+    }
+
+    #[test]
+    fn test_load_broken_file() {
+        assert!(load("{\"diagram\": []}".as_bytes()).is_err());
+    }
+
+    #[test]
+    fn test_load_inconsistent_matrix() {
+        let mut file = Vec::new();
+        save(&mut file, &LineGH::new(), &MatrixPane::new(2, 2, Cell::new('.'))).unwrap();
+        let saved: serde_json::Value = serde_json::from_slice(&file).unwrap();
+        let broken = |change: &dyn Fn(&mut serde_json::Value)| {
+            let mut session = saved.clone();
+            change(&mut session["matrix"]);
+            load(session.to_string().as_bytes()).map(|_| ()).unwrap_err()
+        };
+
+        let err = broken(&|matrix| matrix["size"] = serde_json::json!([0, 1]));
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(err.to_string(), "a 0x1 matrix has 4 nodes");
+        assert_eq!(broken(&|matrix| matrix["size"] = serde_json::json!([3, 2])).to_string(), "a 3x2 matrix has 4 nodes");
+        assert_eq!(broken(&|matrix| matrix["node_list"][3] = serde_json::json!(9)).to_string(), "node 9 of the matrix isn't in its graph");
+
+        // the matrix of a new session has no nodes at all
+        let mut file = Vec::new();
+        save(&mut file, &LineGH::new(), &MatrixPane::new(0, 0, Cell::default())).unwrap();
+        assert_eq!(load(file.as_slice()).unwrap().1.pane().to_string(), "");
+    }
+}